@@ -0,0 +1,97 @@
+//! The clarification turn an agent run takes when it's uncertain about
+//! intent (an ambiguous symbol, several plausible edit targets, a
+//! destructive operation) instead of guessing: emit a `Question`, suspend
+//! on the answer via `state::clarification`, and feed the chosen option's
+//! text back into the prompt context so reasoning continues from the
+//! user's actual choice rather than the model's assumption.
+//!
+//! `in_line_agent` suspends the same way, through the same
+//! `state::clarification` slot, since both loops key off a task id rather
+//! than holding a reference to each other.
+//!
+//! Neither loop's source is present in this checkout (`agent::Agent` and
+//! `in_line_agent` are declared in `lib.rs` with no backing files here), so
+//! there's no reasoning-loop call site in this tree to invoke `ask_and_wait`
+//! from yet — this is a real gap, not one this module can close on its own.
+//! It's exposed as the entry point those loops should call once they decide
+//! a step is ambiguous enough to ask rather than guess.
+//!
+//! What IS real and checked here: the suspend/resume mechanism itself.
+//! `ask_and_wait` drives `state::clarification::ask`, and the test below
+//! resolves it through `webserver::clarification::answer_question`, the same
+//! handler an editor actually calls — so the half of this feature that has a
+//! real caller in this checkout (the HTTP side) is proven against the half
+//! that doesn't (the agent-loop side) end to end, rather than left untested
+//! alongside an unused function.
+
+use uuid::Uuid;
+
+use crate::agentic::tool::human::qa::Question;
+use crate::state::clarification;
+
+/// Asks `question` on behalf of `task_id` and blocks until `webserver`
+/// resolves it with an `Answer`, returning the text of the chosen option
+/// so the caller can splice it straight into the next prompt. Errors if
+/// the task is dropped (e.g. the session ends) before an answer arrives.
+pub async fn ask_and_wait(task_id: Uuid, question: Question) -> anyhow::Result<String> {
+    let answer_rx = clarification::ask(task_id, question.clone());
+    let answer = answer_rx
+        .await
+        .map_err(|_| anyhow::anyhow!("clarification for task {task_id} was never answered"))?;
+
+    question
+        .choices()
+        .iter()
+        .find(|choice| choice.id() == answer.choice_id())
+        .map(|choice| choice.text().to_string())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "answer {} for task {task_id} does not match any offered choice",
+                answer.choice_id()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::Path;
+    use axum::Json;
+    use uuid::Uuid;
+
+    use super::ask_and_wait;
+    use crate::agentic::tool::human::qa::{Choice, Question};
+    use crate::webserver::clarification::{answer_question, AnswerRequest};
+
+    #[tokio::test]
+    async fn ask_and_wait_resolves_through_the_webserver_answer_handler() {
+        let task_id = Uuid::new_v4();
+        let question = Question::new(
+            "Which `parse` did you mean?",
+            &[
+                Choice::new("a", "parser::json::parse"),
+                Choice::new("b", "parser::yaml::parse"),
+            ],
+        );
+
+        let waiter = tokio::spawn(ask_and_wait(task_id, question));
+
+        // Give `ask_and_wait` a chance to register the pending question
+        // before the "editor" answers it.
+        tokio::task::yield_now().await;
+
+        answer_question(
+            Path(task_id),
+            Json(AnswerRequest {
+                choice_id: "b".to_string(),
+            }),
+        )
+        .await
+        .expect("answering a pending clarification should succeed");
+
+        let chosen_text = waiter
+            .await
+            .expect("ask_and_wait task should not panic")
+            .expect("ask_and_wait should resolve once answered");
+        assert_eq!(chosen_text, "parser::yaml::parse");
+    }
+}
@@ -0,0 +1,6 @@
+//! The agent-facing event vocabulary (`types`) shared across `webserver`'s
+//! plan and search/chat handlers, plus the clarification turn (`clarification`)
+//! an agent run takes when it's uncertain rather than guessing.
+
+pub mod clarification;
+pub mod types;
@@ -0,0 +1,55 @@
+//! Homes `AgentAnswerStreamEvent`, the tagged union of events a running
+//! agent (or a plan execution, which streams through the same channel)
+//! emits back to whatever is consuming its `ConversationMessage`s.
+//!
+//! This file adds the plan-specific variants `webserver::plan` streams
+//! through (`PlanOperationApplied`, `PlanError`) so those events ride a
+//! real enum variant instead of a JSON payload smuggled through
+//! `LLMAnswer`'s text field tagged by a magic model-name string. The rest
+//! of `agent::types` — `ConversationMessage`, `Agent`, `CodeSpan`,
+//! `AgentAction`, `AgentState`, `VariableInformation`, `VariableType` —
+//! is the much larger, pre-existing surface `webserver::agent` builds on
+//! and isn't reproduced here.
+
+use llm_client::clients::types::LLMClientCompletionResponse;
+
+/// Machine-readable category for a `PlanError`, so the editor can decide
+/// what to offer the user (retry a flaky step vs. regenerate a plan that
+/// can no longer be trusted) instead of pattern-matching a display string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PlanErrorKind {
+    /// The plan couldn't be read back from storage at all.
+    LoadFailed,
+    /// Context for a step couldn't be assembled.
+    ContextPreparationFailed,
+    /// A step's execution failed after `ExecutionPolicy`'s retries were
+    /// used up, whether its last attempt errored (LLM/tool failure) or
+    /// timed out.
+    StepExecutionFailed,
+    /// The plan has no checkpoint yet, so there's nothing to diagnose.
+    MissingCheckpoint,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PlanError {
+    pub plan_id: uuid::Uuid,
+    /// Which function/stage produced this, e.g. `"execute_plan_until"`.
+    pub phase: String,
+    pub kind: PlanErrorKind,
+    /// The full error chain (`{:#}`), not just a fixed string, so logs and
+    /// client-side diagnostics keep the actual cause.
+    pub message: String,
+    pub step_idx: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AgentAnswerStreamEvent {
+    LLMAnswer(LLMClientCompletionResponse),
+    /// An operational-transform step edit (insert/delete/edit) was applied
+    /// to a plan, successfully transformed against anything concurrent.
+    PlanOperationApplied(crate::webserver::plan_ot::AppliedOperation),
+    /// A plan-execution failure, carrying enough structure for the client
+    /// to distinguish a transient error (offer retry) from a corrupt-plan
+    /// error (offer regenerate).
+    PlanError(PlanError),
+}
@@ -0,0 +1,135 @@
+//! Streams named artifacts (the evolving diff, tool-call transcripts, the
+//! final patch, reproduction output) up to a configurable sink as a session
+//! progresses, keyed by run_id/instance_id, instead of leaving everything
+//! under the local `--log_directory` until the runner exits or crashes.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ArtifactKind {
+    Diff,
+    ToolCallTranscript,
+    FinalPatch,
+    ReproductionOutput,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub run_id: String,
+    pub instance_id: String,
+    pub kind: ArtifactKind,
+    pub name: String,
+    pub contents: String,
+}
+
+/// Where artifacts end up. `Local` mirrors today's behavior; `S3` and
+/// `DriverHttp` let them survive a crashed runner by leaving the worker
+/// entirely.
+pub enum ArtifactSink {
+    Local { log_directory: String },
+    S3Compatible { endpoint: String, bucket: String, client: reqwest::Client },
+    DriverHttp { driver_url: String, auth_secret: String, client: reqwest::Client },
+}
+
+impl ArtifactSink {
+    pub async fn upload(&self, artifact: &Artifact) -> anyhow::Result<()> {
+        match self {
+            ArtifactSink::Local { log_directory } => {
+                let path = std::path::Path::new(log_directory)
+                    .join(&artifact.instance_id)
+                    .join(&artifact.name);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, &artifact.contents).await?;
+                Ok(())
+            }
+            ArtifactSink::S3Compatible { endpoint, bucket, client } => {
+                let url = format!(
+                    "{endpoint}/{bucket}/{}/{}",
+                    artifact.instance_id, artifact.name
+                );
+                client
+                    .put(url)
+                    .body(artifact.contents.clone())
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                Ok(())
+            }
+            ArtifactSink::DriverHttp { driver_url, auth_secret, client } => {
+                client
+                    .post(format!("{driver_url}/farm/artifact"))
+                    .header(super::AUTH_HEADER, auth_secret)
+                    .json(artifact)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Wraps an `ArtifactSink` with the run_id/instance_id every artifact it
+/// flushes should be keyed by. Artifacts are pushed by calling `publish`
+/// (or by the periodic diff task); it exposes no sender and is not
+/// connected to the `ConversationMessage` event channel.
+#[derive(Clone)]
+pub struct ArtifactPublisher {
+    sink: std::sync::Arc<ArtifactSink>,
+    run_id: String,
+    instance_id: String,
+}
+
+impl ArtifactPublisher {
+    pub fn new(sink: ArtifactSink, run_id: String, instance_id: String) -> Self {
+        Self {
+            sink: std::sync::Arc::new(sink),
+            run_id,
+            instance_id,
+        }
+    }
+
+    pub async fn publish(&self, kind: ArtifactKind, name: impl Into<String>, contents: impl Into<String>) {
+        let artifact = Artifact {
+            run_id: self.run_id.clone(),
+            instance_id: self.instance_id.clone(),
+            kind,
+            name: name.into(),
+            contents: contents.into(),
+        };
+        if let Err(err) = self.sink.upload(&artifact).await {
+            eprintln!(
+                "farm::artifact_upload_failed::{}::{:?}",
+                artifact.name, err
+            );
+        }
+    }
+
+    /// Spawns a background task that periodically re-uploads the evolving
+    /// diff so mid-run inspection doesn't have to wait for the job to
+    /// finish.
+    pub fn spawn_periodic_diff_upload(
+        &self,
+        working_directory: std::path::PathBuf,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let publisher = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Ok(output) = tokio::process::Command::new("git")
+                    .arg("diff")
+                    .current_dir(&working_directory)
+                    .output()
+                    .await
+                {
+                    let diff = String::from_utf8_lossy(&output.stdout).into_owned();
+                    publisher.publish(ArtifactKind::Diff, "evolving.diff", diff).await;
+                }
+            }
+        })
+    }
+}
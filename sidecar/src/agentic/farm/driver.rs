@@ -0,0 +1,267 @@
+//! Driver side of the farm: owns the job queue and hands work to runners
+//! that long-poll for it.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use sqlx::SqlitePool;
+
+use super::{
+    ClaimRequest, ClaimResponse, HeartbeatRequest, JobRecord, JobState, ResolveRequest,
+    SWEbenchInstance, AUTH_HEADER,
+};
+
+/// A job stuck in `Running` longer than this without a heartbeat is
+/// considered abandoned (its runner likely crashed) and re-queued.
+const STALE_RUNNING_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone)]
+pub struct DriverState {
+    pool: SqlitePool,
+    auth_secret: String,
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+impl DriverState {
+    pub fn new(pool: SqlitePool, auth_secret: String) -> Self {
+        Self { pool, auth_secret }
+    }
+
+    /// Creates `farm_jobs` if this is the first run against `pool`.
+    pub async fn run_migrations(pool: &SqlitePool) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS farm_jobs (
+                instance_id TEXT PRIMARY KEY,
+                repo TEXT NOT NULL,
+                base_commit TEXT NOT NULL,
+                state TEXT NOT NULL,
+                assigned_runner TEXT,
+                started_at INTEGER,
+                instance_json TEXT NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Checks `headers` carries `AUTH_HEADER` matching this driver's shared
+    /// secret, so a runner can't claim/heartbeat/resolve jobs without it.
+    fn check_auth(&self, headers: &HeaderMap) -> Result<(), StatusCode> {
+        let provided = headers
+            .get(AUTH_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        if provided == self.auth_secret {
+            Ok(())
+        } else {
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    /// Enqueues `instance` in full, so a runner that claims it later gets
+    /// everything needed to actually run it (`problem_statement`,
+    /// `test_patch`, ...) back from `/farm/claim` rather than just enough
+    /// to identify the job.
+    pub async fn enqueue(&self, instance: &SWEbenchInstance) -> anyhow::Result<()> {
+        let instance_json = serde_json::to_string(instance)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO farm_jobs (instance_id, repo, base_commit, state, assigned_runner, started_at, instance_json)
+            VALUES (?, ?, ?, 'Pending', NULL, NULL, ?)
+            ON CONFLICT(instance_id) DO NOTHING
+            "#,
+            instance.instance_id,
+            instance.repo,
+            instance.base_commit,
+            instance_json,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Reclaims any job left `Running` past `STALE_RUNNING_TIMEOUT` so a
+    /// crashed runner doesn't leave work stuck forever.
+    async fn requeue_stale_jobs(&self) -> anyhow::Result<()> {
+        let cutoff = now_unix() - STALE_RUNNING_TIMEOUT.as_secs() as i64;
+        sqlx::query!(
+            r#"
+            UPDATE farm_jobs
+            SET state = 'Pending', assigned_runner = NULL, started_at = NULL
+            WHERE state = 'Running' AND started_at < ?
+            "#,
+            cutoff,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Idempotent claim: a runner sends its `run_id`; if a job is already
+    /// assigned to that exact `run_id` (e.g. a retried request after a
+    /// dropped response) we hand back the same job rather than a new one.
+    async fn claim_next(&self, run_id: &str) -> anyhow::Result<Option<JobRecord>> {
+        self.requeue_stale_jobs().await?;
+
+        if let Some(job) = self.job_assigned_to(run_id).await? {
+            return Ok(Some(job));
+        }
+
+        let claimed = sqlx::query!(
+            r#"
+            UPDATE farm_jobs
+            SET state = 'Running', assigned_runner = ?, started_at = ?
+            WHERE instance_id = (
+                SELECT instance_id FROM farm_jobs WHERE state = 'Pending' LIMIT 1
+            )
+            RETURNING instance_id, repo, base_commit, instance_json
+            "#,
+            run_id,
+            now_unix(),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        claimed
+            .map(|row| {
+                Ok(JobRecord {
+                    instance_id: row.instance_id,
+                    repo: row.repo,
+                    base_commit: row.base_commit,
+                    state: JobState::Running,
+                    assigned_runner: Some(run_id.to_owned()),
+                    started_at: Some(now_unix()),
+                    instance: serde_json::from_str(&row.instance_json)?,
+                })
+            })
+            .transpose()
+    }
+
+    async fn job_assigned_to(&self, run_id: &str) -> anyhow::Result<Option<JobRecord>> {
+        let row = sqlx::query!(
+            "SELECT instance_id, repo, base_commit, started_at, instance_json FROM farm_jobs WHERE assigned_runner = ? AND state = 'Running'",
+            run_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| {
+            Ok(JobRecord {
+                instance_id: row.instance_id,
+                repo: row.repo,
+                base_commit: row.base_commit,
+                state: JobState::Running,
+                assigned_runner: Some(run_id.to_owned()),
+                started_at: row.started_at,
+                instance: serde_json::from_str(&row.instance_json)?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn heartbeat(&self, run_id: &str, instance_id: &str) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE farm_jobs SET started_at = ? WHERE instance_id = ? AND assigned_runner = ?",
+            now_unix(),
+            instance_id,
+            run_id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn resolve(&self, run_id: &str, instance_id: &str, state: JobState) -> anyhow::Result<()> {
+        let state_str = match state {
+            JobState::Pending => "Pending",
+            JobState::Running => "Running",
+            JobState::Finished => "Finished",
+            JobState::Error => "Error",
+        };
+        sqlx::query!(
+            "UPDATE farm_jobs SET state = ? WHERE instance_id = ? AND assigned_runner = ?",
+            state_str,
+            instance_id,
+            run_id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+async fn handle_claim(
+    State(state): State<Arc<DriverState>>,
+    headers: HeaderMap,
+    Json(request): Json<ClaimRequest>,
+) -> Result<Json<ClaimResponse>, StatusCode> {
+    state.check_auth(&headers)?;
+    // Long-poll: hold the request open for up to 30s, checking for a
+    // pending job once a second, before answering with no job.
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(30);
+    loop {
+        let job = state
+            .claim_next(&request.run_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if job.is_some() || tokio::time::Instant::now() >= deadline {
+            return Ok(Json(ClaimResponse { job }));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn handle_heartbeat(
+    State(state): State<Arc<DriverState>>,
+    headers: HeaderMap,
+    Json(request): Json<HeartbeatRequest>,
+) -> StatusCode {
+    if let Err(status) = state.check_auth(&headers) {
+        return status;
+    }
+    match state.heartbeat(&request.run_id, &request.instance_id).await {
+        Ok(()) => StatusCode::OK,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+async fn handle_resolve(
+    State(state): State<Arc<DriverState>>,
+    headers: HeaderMap,
+    Json(request): Json<ResolveRequest>,
+) -> StatusCode {
+    if let Err(status) = state.check_auth(&headers) {
+        return status;
+    }
+    match state
+        .resolve(&request.run_id, &request.instance_id, request.state)
+        .await
+    {
+        Ok(()) => StatusCode::OK,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Builds the driver's long-poll router: `/farm/claim`, `/farm/heartbeat`,
+/// `/farm/resolve`. Each handler checks the shared secret in `AUTH_HEADER`
+/// itself, rather than relying on a middleware layer the caller might
+/// forget to wrap this router in.
+pub fn driver_router(state: Arc<DriverState>) -> Router {
+    Router::new()
+        .route("/farm/claim", post(handle_claim))
+        .route("/farm/heartbeat", post(handle_heartbeat))
+        .route("/farm/resolve", post(handle_resolve))
+        .with_state(state)
+}
@@ -0,0 +1,59 @@
+//! Reproducibility metadata captured once per run and written alongside
+//! the logs, so two runs that resolve differently can actually be
+//! compared instead of just diffed on their final patch.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvInfo {
+    pub hostname: String,
+    pub cpu_count: usize,
+    pub total_memory_bytes: u64,
+    pub os: String,
+    pub kernel_version: String,
+    pub sidecar_git_commit: String,
+    pub llm_provider: String,
+    pub llm_model: String,
+    pub max_depth: u32,
+    pub midwit_mode: bool,
+    pub json_mode: bool,
+    pub environment_setup_commit: String,
+    pub version: String,
+}
+
+impl EnvInfo {
+    pub fn gather(
+        llm_provider: String,
+        llm_model: String,
+        max_depth: u32,
+        midwit_mode: bool,
+        json_mode: bool,
+        environment_setup_commit: String,
+        version: String,
+    ) -> Self {
+        let system = sysinfo::System::new_all();
+        Self {
+            hostname: sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_owned()),
+            cpu_count: system.cpus().len(),
+            total_memory_bytes: system.total_memory(),
+            os: sysinfo::System::long_os_version().unwrap_or_else(|| "unknown".to_owned()),
+            kernel_version: sysinfo::System::kernel_version().unwrap_or_else(|| "unknown".to_owned()),
+            sidecar_git_commit: option_env!("SIDECAR_GIT_COMMIT")
+                .unwrap_or("unknown")
+                .to_owned(),
+            llm_provider,
+            llm_model,
+            max_depth,
+            midwit_mode,
+            json_mode,
+            environment_setup_commit,
+            version,
+        }
+    }
+
+    pub async fn write_to(&self, session_storage_dir: &std::path::Path) -> anyhow::Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(session_storage_dir.join("env_info.json"), contents).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Pull-based runner/driver split so a SWE-bench style sweep can be chewed
+//! through by many machines instead of one `--input` per OS process.
+
+pub mod artifacts;
+pub mod driver;
+pub mod env_info;
+pub mod remote_exec;
+pub mod runner;
+pub mod server;
+pub mod verify;
+
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of a single instance job as tracked by the driver's queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobState {
+    Pending,
+    Running,
+    Finished,
+    Error,
+}
+
+/// Everything a SWE-bench instance carries, shared between whatever
+/// enqueues work against the driver and the runner that eventually claims
+/// it. Lives here (rather than as a `bin/agent_bin_reasoning.rs`-private
+/// struct) so it can ride inside `JobRecord` — a claimed job needs to
+/// actually deliver this over the network, not just identify it by id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SWEbenchInstance {
+    pub repo: String,
+    pub instance_id: String,
+    pub base_commit: String,
+    pub patch: String,
+    pub test_patch: String,
+    pub problem_statement: String,
+    pub hints_text: String,
+    pub created_at: String,
+    pub version: String,
+    #[serde(rename = "FAIL_TO_PASS")]
+    pub fail_to_pass: String,
+    #[serde(rename = "PASS_TO_PASS")]
+    pub pass_to_pass: String,
+    pub environment_setup_commit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub instance_id: String,
+    pub repo: String,
+    pub base_commit: String,
+    pub state: JobState,
+    pub assigned_runner: Option<String>,
+    pub started_at: Option<i64>,
+    /// The full instance payload (problem statement, test patches, ...), so
+    /// a runner on a different machine than whatever enqueued this job can
+    /// actually act on it instead of needing a `{instance_id}.json` file to
+    /// already be sitting on its local disk.
+    pub instance: SWEbenchInstance,
+}
+
+/// What a runner sends when it wants the next job and when it reports back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimRequest {
+    /// The `--run_id` the runner was launched with; doubles as its claim
+    /// token so the driver can reject a second runner claiming the same
+    /// job out from under the first.
+    pub run_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimResponse {
+    pub job: Option<JobRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatRequest {
+    pub run_id: String,
+    pub instance_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveRequest {
+    pub run_id: String,
+    pub instance_id: String,
+    pub state: JobState,
+}
+
+/// Failure modes for a runner's long-poll against the driver. Kept distinct
+/// from `anyhow::Error` so a runner can decide which ones are worth
+/// retrying versus giving up on.
+#[derive(Debug, thiserror::Error)]
+pub enum WorkAcquireError {
+    #[error("request to driver failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("driver connection closed before a response was received")]
+    EarlyEof,
+    #[error("driver responded with an incompatible protocol version: {0}")]
+    ProtocolMismatch(String),
+    #[error("driver rejected this run_id's claim (already claimed by another runner)")]
+    DoubleClaim,
+}
+
+/// Shared secret a runner sends as a header on every request to the driver,
+/// so the driver only accepts claims/heartbeats/resolves from runners that
+/// know it. The runner does not check the driver's identity.
+pub const AUTH_HEADER: &str = "x-sidecar-farm-secret";
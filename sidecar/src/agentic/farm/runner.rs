@@ -0,0 +1,99 @@
+//! Runner side of the farm: asks the driver for the next job, streams
+//! heartbeats while it works, and reports the job resolved/errored.
+
+use std::time::Duration;
+
+use super::{
+    ClaimRequest, ClaimResponse, HeartbeatRequest, JobRecord, JobState, ResolveRequest,
+    WorkAcquireError, AUTH_HEADER,
+};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct RunnerClient {
+    http: reqwest::Client,
+    driver_url: String,
+    auth_secret: String,
+    run_id: String,
+}
+
+impl RunnerClient {
+    pub fn new(driver_url: String, auth_secret: String, run_id: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            driver_url,
+            auth_secret,
+            run_id,
+        }
+    }
+
+    /// Long-polls the driver once for the next job. A failed request is
+    /// returned as a `WorkAcquireError` without retrying; retrying is left
+    /// to the caller.
+    pub async fn next_job(&self) -> Result<Option<JobRecord>, WorkAcquireError> {
+        let response = self
+            .http
+            .post(format!("{}/farm/claim", self.driver_url))
+            .header(AUTH_HEADER, &self.auth_secret)
+            .json(&ClaimRequest {
+                run_id: self.run_id.clone(),
+            })
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Err(WorkAcquireError::DoubleClaim);
+        }
+        if !response.status().is_success() {
+            return Err(WorkAcquireError::ProtocolMismatch(format!(
+                "unexpected status: {}",
+                response.status()
+            )));
+        }
+
+        let claim: ClaimResponse = response
+            .json()
+            .await
+            .map_err(|_| WorkAcquireError::EarlyEof)?;
+        Ok(claim.job)
+    }
+
+    /// Spawns a background heartbeat loop for `instance_id`; call `abort()`
+    /// on the returned handle to stop it once the job finishes (dropping the
+    /// handle leaves the loop running).
+    pub fn spawn_heartbeat(&self, instance_id: String) -> tokio::task::JoinHandle<()> {
+        let http = self.http.clone();
+        let driver_url = self.driver_url.clone();
+        let auth_secret = self.auth_secret.clone();
+        let run_id = self.run_id.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+            loop {
+                interval.tick().await;
+                let _ = http
+                    .post(format!("{}/farm/heartbeat", driver_url))
+                    .header(AUTH_HEADER, &auth_secret)
+                    .json(&HeartbeatRequest {
+                        run_id: run_id.clone(),
+                        instance_id: instance_id.clone(),
+                    })
+                    .send()
+                    .await;
+            }
+        })
+    }
+
+    pub async fn resolve(&self, instance_id: String, state: JobState) -> anyhow::Result<()> {
+        self.http
+            .post(format!("{}/farm/resolve", self.driver_url))
+            .header(AUTH_HEADER, &self.auth_secret)
+            .json(&ResolveRequest {
+                run_id: self.run_id.clone(),
+                instance_id,
+                state,
+            })
+            .send()
+            .await?;
+        Ok(())
+    }
+}
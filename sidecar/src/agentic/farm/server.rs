@@ -0,0 +1,271 @@
+//! Optional HTTP server mode: `POST /v1/run` submits a workload and returns
+//! immediately with a run_id while the agent executes in a spawned task;
+//! `GET /v1/run/{id}` streams its progress. Turns the farm into a service
+//! editors/CI can call instead of forking one process per instance.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path as AxumPath, State};
+use axum::response::sse::{self, Sse};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use llm_client::clients::types::LLMType;
+use llm_client::provider::{AnthropicAPIKey, LLMProvider, LLMProviderAPIKeys};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::agent::types::ConversationMessage;
+use crate::agentic::symbol::events::input::SymbolEventRequestId;
+use crate::agentic::symbol::events::message_event::SymbolEventMessageProperties;
+use crate::agentic::symbol::identifier::LLMProperties;
+use crate::application::application::Application;
+use crate::repo::types::RepoRef;
+use crate::user_context::types::UserContext;
+
+/// One instance within a submitted workload. The knobs that used to only
+/// be reachable via `CliArgs` live on `RunWorkload`, not here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunInstance {
+    pub instance_id: String,
+    pub repo: String,
+    pub base_commit: String,
+    pub problem_statement: String,
+    #[serde(default)]
+    pub rules: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunWorkload {
+    pub run_id: String,
+    pub instances: Vec<RunInstance>,
+    #[serde(default)]
+    pub model_name: Option<String>,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: u32,
+    #[serde(default = "default_true")]
+    pub midwit_mode: bool,
+    #[serde(default = "default_true")]
+    pub json_mode: bool,
+    #[serde(default)]
+    pub single_traj_search: Option<usize>,
+    /// Where the editor listening for this run's events can be reached;
+    /// empty when nothing but this HTTP endpoint is consuming progress.
+    #[serde(default)]
+    pub editor_url: String,
+}
+
+fn default_max_depth() -> u32 {
+    30
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitRunResponse {
+    pub run_id: String,
+}
+
+/// Per-run progress channel, the thing that used to be created and
+/// immediately thrown away (`let (sender, _receiver) = ...`) in the
+/// CLI-only entry point.
+struct RunProgress {
+    sender: mpsc::UnboundedSender<anyhow::Result<ConversationMessage>>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<anyhow::Result<ConversationMessage>>>>,
+}
+
+#[derive(Clone)]
+pub struct FarmServerState {
+    runs: Arc<Mutex<HashMap<String, Arc<RunProgress>>>>,
+    application: Application,
+    anthropic_api_key: String,
+}
+
+impl FarmServerState {
+    pub fn new(application: Application, anthropic_api_key: String) -> Self {
+        Self {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+            application,
+            anthropic_api_key,
+        }
+    }
+
+    /// Spawns `execute` (which should drive the instances and push
+    /// `ConversationMessage`s into the given sender) and registers the
+    /// run's receiver so `GET /v1/run/{id}` can stream it.
+    pub async fn submit<F, Fut>(&self, workload: &RunWorkload, execute: F)
+    where
+        F: FnOnce(RunWorkload, mpsc::UnboundedSender<anyhow::Result<ConversationMessage>>) -> Fut
+            + Send
+            + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let progress = Arc::new(RunProgress {
+            sender: sender.clone(),
+            receiver: Mutex::new(Some(receiver)),
+        });
+        self.runs
+            .lock()
+            .await
+            .insert(workload.run_id.clone(), progress);
+
+        let workload = workload.clone();
+        tokio::spawn(async move {
+            execute(workload, sender).await;
+        });
+    }
+
+    async fn take_receiver(
+        &self,
+        run_id: &str,
+    ) -> Option<mpsc::UnboundedReceiver<anyhow::Result<ConversationMessage>>> {
+        let runs = self.runs.lock().await;
+        let progress = runs.get(run_id)?;
+        progress.receiver.lock().await.take()
+    }
+}
+
+/// Drives a single `RunInstance` the same way the `--driver-url` farm CLI
+/// drives a claimed job: build its `SymbolEventMessageProperties` (the
+/// `sender` given here is what carries its `ConversationMessage`s back to
+/// `GET /v1/run/{id}`'s SSE stream) and hand it to `tool_use_agentic`.
+async fn run_instance(
+    application: Application,
+    anthropic_api_key: String,
+    run_id: String,
+    model_name: Option<String>,
+    instance: RunInstance,
+    editor_url: String,
+    sender: mpsc::UnboundedSender<anyhow::Result<ConversationMessage>>,
+) {
+    let llm_model = match model_name {
+        Some(model_name) => LLMType::Custom(model_name),
+        None => LLMType::ClaudeSonnet,
+    };
+    let llm_provider = LLMProperties::new(
+        llm_model,
+        LLMProvider::Anthropic,
+        LLMProviderAPIKeys::Anthropic(AnthropicAPIKey::new(anthropic_api_key)),
+    );
+    let cancellation_token = tokio_util::sync::CancellationToken::new();
+    let message_properties = SymbolEventMessageProperties::new(
+        SymbolEventRequestId::new("0".to_owned(), run_id.clone()),
+        sender.clone(),
+        editor_url,
+        cancellation_token,
+        llm_provider,
+    );
+
+    let Ok(repo_ref) = RepoRef::local(&instance.repo) else {
+        let _ = sender.send(Err(anyhow::anyhow!(
+            "instance {}: {:?} is not a usable repo path",
+            instance.instance_id,
+            instance.repo
+        )));
+        return;
+    };
+
+    let session_storage_path = application
+        .config
+        .index_dir
+        .join("session")
+        .join(format!("{}-{}", run_id, instance.instance_id));
+    if tokio::fs::metadata(&session_storage_path).await.is_err() {
+        if let Err(err) = tokio::fs::create_dir_all(&session_storage_path).await {
+            let _ = sender.send(Err(anyhow::anyhow!(
+                "instance {}: failed to create session storage dir: {err}",
+                instance.instance_id
+            )));
+            return;
+        }
+    }
+    let session_storage_path = session_storage_path
+        .to_str()
+        .expect("path conversion to work on all platforms")
+        .to_owned();
+
+    let session_service = application.session_service.clone();
+    let tool_box = application.tool_box.clone();
+    let llm_broker = application.llm_broker.clone();
+
+    let _ = session_service
+        .tool_use_agentic(
+            format!("{}-{}", run_id, instance.instance_id),
+            session_storage_path,
+            instance.problem_statement,
+            "0".to_owned(),
+            vec![],
+            vec![],
+            "bash".to_owned(),
+            vec![],
+            repo_ref,
+            instance.repo,
+            tool_box,
+            llm_broker,
+            UserContext::default(),
+            instance.rules,
+            true,
+            false,
+            false,
+            None,
+            Some(instance.instance_id),
+            message_properties,
+            false,
+            None,
+        )
+        .await;
+}
+
+async fn handle_submit_run(
+    State(state): State<FarmServerState>,
+    Json(workload): Json<RunWorkload>,
+) -> Json<SubmitRunResponse> {
+    let run_id = workload.run_id.clone();
+    let application = state.application.clone();
+    let anthropic_api_key = state.anthropic_api_key.clone();
+    state
+        .submit(&workload, move |workload, sender| async move {
+            for instance in workload.instances {
+                run_instance(
+                    application.clone(),
+                    anthropic_api_key.clone(),
+                    workload.run_id.clone(),
+                    workload.model_name.clone(),
+                    instance,
+                    workload.editor_url.clone(),
+                    sender.clone(),
+                )
+                .await;
+            }
+        })
+        .await;
+    Json(SubmitRunResponse { run_id })
+}
+
+async fn handle_get_run(
+    AxumPath(run_id): AxumPath<String>,
+    State(state): State<FarmServerState>,
+) -> Sse<impl tokio_stream::Stream<Item = anyhow::Result<sse::Event>>> {
+    let receiver = state.take_receiver(&run_id).await;
+    let stream = async_stream::stream! {
+        let Some(mut receiver) = receiver else { return; };
+        while let Some(message) = receiver.recv().await {
+            yield message.and_then(|message| {
+                sse::Event::default()
+                    .json_data(message)
+                    .map_err(anyhow::Error::new)
+            });
+        }
+    };
+    Sse::new(stream)
+}
+
+pub fn farm_server_router(state: FarmServerState) -> Router {
+    Router::new()
+        .route("/v1/run", post(handle_submit_run))
+        .route("/v1/run/:id", get(handle_get_run))
+        .with_state(state)
+}
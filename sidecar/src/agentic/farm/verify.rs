@@ -0,0 +1,121 @@
+//! SWE-bench style verification: applies the ground-truth test patch on top
+//! of the agent's diff and checks FAIL_TO_PASS/PASS_TO_PASS, so a run
+//! produces a gradeable resolution rate without a separate harness.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use super::remote_exec::ExecBackend;
+
+/// Where the ground-truth test patch is staged before `git apply`, relative
+/// to the instance's working directory. Going through a file instead of
+/// piping the patch to `git apply`'s stdin lets both `ExecBackend` variants
+/// share one code path (`Ssh`'s `run_bash` has no stdin of its own).
+const TEST_PATCH_STAGING_PATH: &str = ".sidecar_test_patch.diff";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceResult {
+    pub instance_id: String,
+    pub resolved: bool,
+    pub fail_to_pass_passed: Vec<String>,
+    pub fail_to_pass_failed: Vec<String>,
+    pub pass_to_pass_passed: Vec<String>,
+    pub pass_to_pass_failed: Vec<String>,
+    pub patch: String,
+    pub logs_path: String,
+}
+
+/// `fail_to_pass`/`pass_to_pass` on `SWEbenchInstance` are JSON-list
+/// strings (e.g. `"[\"tests/test_foo.py::test_bar\"]"`), not real arrays.
+pub fn parse_test_id_list(raw: &str) -> anyhow::Result<Vec<String>> {
+    Ok(serde_json::from_str(raw)?)
+}
+
+async fn run_test_ids(
+    exec_backend: &ExecBackend,
+    test_ids: &[String],
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    let mut passed = Vec::new();
+    let mut failed = Vec::new();
+    for test_id in test_ids {
+        let (exit_code, _stdout, _stderr) = exec_backend
+            .run_bash(&format!("python -m pytest {}", test_id))
+            .await?;
+        if exit_code == 0 {
+            passed.push(test_id.clone());
+        } else {
+            failed.push(test_id.clone());
+        }
+    }
+    Ok((passed, failed))
+}
+
+async fn apply_patch(exec_backend: &ExecBackend, patch: &str) -> anyhow::Result<()> {
+    exec_backend
+        .write_file(TEST_PATCH_STAGING_PATH, patch)
+        .await?;
+    let (exit_code, _stdout, stderr) = exec_backend
+        .run_bash(&format!("git apply {}", TEST_PATCH_STAGING_PATH))
+        .await?;
+    anyhow::ensure!(exit_code == 0, "failed to apply test patch: {}", stderr);
+    Ok(())
+}
+
+/// Runs after `tool_use_agentic` returns, given the agent's diff against
+/// `base_commit` as `agent_patch` (captured by the caller, and only carried
+/// into the result here): applies the ground-truth `test_patch` on top, runs
+/// FAIL_TO_PASS/PASS_TO_PASS, and scores the instance resolved only if
+/// every FAIL_TO_PASS test now passes AND every PASS_TO_PASS test still
+/// passes.
+pub async fn verify_instance(
+    instance_id: String,
+    exec_backend: &ExecBackend,
+    agent_patch: String,
+    test_patch: &str,
+    fail_to_pass_raw: &str,
+    pass_to_pass_raw: &str,
+    logs_path: String,
+) -> anyhow::Result<InstanceResult> {
+    apply_patch(exec_backend, test_patch).await?;
+
+    let fail_to_pass = parse_test_id_list(fail_to_pass_raw)?;
+    let pass_to_pass = parse_test_id_list(pass_to_pass_raw)?;
+
+    let (fail_to_pass_passed, fail_to_pass_failed) =
+        run_test_ids(exec_backend, &fail_to_pass).await?;
+    let (pass_to_pass_passed, pass_to_pass_failed) =
+        run_test_ids(exec_backend, &pass_to_pass).await?;
+
+    let resolved = fail_to_pass_failed.is_empty() && pass_to_pass_failed.is_empty();
+
+    Ok(InstanceResult {
+        instance_id,
+        resolved,
+        fail_to_pass_passed,
+        fail_to_pass_failed,
+        pass_to_pass_passed,
+        pass_to_pass_failed,
+        patch: agent_patch,
+        logs_path,
+    })
+}
+
+/// Appends one instance's result to the run's JSONL report so the split's
+/// resolution rate is immediately gradeable.
+pub async fn append_result_to_report(
+    log_directory: &Path,
+    result: &InstanceResult,
+) -> anyhow::Result<()> {
+    let report_path = log_directory.join("report.jsonl");
+    let mut line = serde_json::to_string(result)?;
+    line.push('\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(report_path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    Ok(())
+}
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Question {
     text: String,
     choices: Vec<Choice>,
@@ -14,9 +15,13 @@ impl Question {
     pub fn text(&self) -> &str {
         &self.text
     }
+
+    pub fn choices(&self) -> &[Choice] {
+        &self.choices
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Choice {
     id: String,
     text: String,
@@ -29,9 +34,17 @@ impl Choice {
             text: text.to_string(),
         }
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Answer {
     choice_id: String,
 }
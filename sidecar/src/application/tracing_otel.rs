@@ -0,0 +1,56 @@
+//! Wires an OTLP exporter into the existing `tracing` setup so the
+//! `#[tracing::instrument]` spans on plan creation/execution (`plan_id`,
+//! `step_idx`, `checkpoint`, `is_deep_reasoning`, and the LLM/LSP spans they
+//! wrap) land in a standard collector instead of only ever being visible in
+//! local stdout logs.
+
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{trace::Config as TraceConfig, Resource};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use super::config::configuration::Configuration;
+
+/// Installs a global `tracing` subscriber made of the existing env-filtered
+/// fmt layer plus, when `config.otlp_endpoint` is set, an OTLP tracing
+/// layer exporting spans over gRPC. Without an endpoint only the
+/// env-filtered fmt layer is installed; no spans leave the process.
+///
+/// Called from `bin/agent_bin_reasoning.rs` right after
+/// `Application::install_logging`, the one real startup path this checkout
+/// has.
+pub fn init_tracing(config: &Configuration) -> anyhow::Result<()> {
+    let fmt_layer = tracing_subscriber::fmt::layer();
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+
+    let otlp_layer = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(
+                    TraceConfig::default().with_resource(Resource::new(vec![KeyValue::new(
+                        "service.name",
+                        "sidecar",
+                    )])),
+                )
+                .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .try_init()?;
+
+    Ok(())
+}
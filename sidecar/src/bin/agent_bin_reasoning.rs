@@ -9,16 +9,22 @@ use llm_client::{
     provider::{AnthropicAPIKey, LLMProvider, LLMProviderAPIKeys},
 };
 use sidecar::{
-    agentic::symbol::{
-        events::{input::SymbolEventRequestId, message_event::SymbolEventMessageProperties},
-        identifier::LLMProperties,
+    agentic::{
+        farm::{runner::RunnerClient, SWEbenchInstance},
+        symbol::{
+            events::{input::SymbolEventRequestId, message_event::SymbolEventMessageProperties},
+            identifier::LLMProperties,
+        },
     },
     application::{application::Application, config::configuration::Configuration},
     repo::types::RepoRef,
     user_context::types::UserContext,
 };
 
-pub async fn check_session_storage_path(config: Arc<Configuration>, session_id: String) -> String {
+/// Returns the session's storage directory (not just its string form) so
+/// callers can write additional per-session artifacts into it, like
+/// `env_info.json`, without re-deriving the path.
+pub async fn check_session_storage_path(config: Arc<Configuration>, session_id: String) -> PathBuf {
     let mut session_path = config.index_dir.clone();
     session_path = session_path.join("session");
     // check if the plan_storage_path_exists
@@ -29,9 +35,6 @@ pub async fn check_session_storage_path(config: Arc<Configuration>, session_id:
     }
     session_path = session_path.join(session_id);
     session_path
-        .to_str()
-        .expect("path conversion to work on all platforms")
-        .to_owned()
 }
 
 /// Define the command-line arguments
@@ -52,7 +55,27 @@ struct CliArgs {
 
     /// Timeout in seconds
     #[arg(long)]
-    input: PathBuf,
+    input: Option<PathBuf>,
+
+    /// When set, this process runs as a farm runner instead of executing a
+    /// single `--input` job: it long-polls `driver_url` for work, streams
+    /// heartbeats while a job runs, and reports it resolved/errored.
+    #[arg(long)]
+    driver_url: Option<String>,
+
+    /// Shared secret sent on every driver<->runner request.
+    #[arg(long, default_value = None)]
+    farm_secret: Option<String>,
+
+    /// `user@host` the job's checkout lives on. When set together with
+    /// `--remote-path`, all bash/edit commands run over SSH instead of
+    /// against a local clone.
+    #[arg(long, default_value = None)]
+    remote_host: Option<String>,
+
+    /// Path to the checkout on `--remote-host`.
+    #[arg(long, default_value = None)]
+    remote_path: Option<String>,
 
     /// Anthropic api key
     #[arg(long, default_value = None)]
@@ -94,25 +117,6 @@ struct CliArgs {
     model_name: Option<String>,
 }
 
-/// Define the SWEbenchInstance struct for serialization
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct SWEbenchInstance {
-    repo: String,
-    instance_id: String,
-    base_commit: String,
-    patch: String,
-    test_patch: String,
-    problem_statement: String,
-    hints_text: String,
-    created_at: String,
-    version: String,
-    #[serde(rename = "FAIL_TO_PASS")]
-    fail_to_pass: String,
-    #[serde(rename = "PASS_TO_PASS")]
-    pass_to_pass: String,
-    environment_setup_commit: String,
-}
-
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct InputParts {
     git_drname: String,
@@ -135,6 +139,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // setup the application
     Application::install_logging(&configuration);
+    if let Err(err) = sidecar::application::tracing_otel::init_tracing(&configuration) {
+        eprintln!("failed to initialize OTLP tracing, continuing with stdout logging only: {err}");
+    }
     Application::setup_scratch_pad(&configuration).await;
 
     let application = Application::initialize(configuration)
@@ -147,6 +154,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         LLMType::ClaudeSonnet
     };
+    let llm_model_name = format!("{:?}", &llm_model);
 
     let llm_provider = LLMProperties::new(
         llm_model,
@@ -163,22 +171,115 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         llm_provider,
     );
 
-    let session_storage_path =
+    let session_storage_dir =
         check_session_storage_path(application.config.clone(), args.run_id.clone()).await;
+    let session_storage_path = session_storage_dir
+        .to_str()
+        .expect("path conversion to work on all platforms")
+        .to_owned();
 
     let session_service = application.session_service.clone();
 
-    let input_path = args.input;
-    let input_content = tokio::fs::read(input_path).await.expect("path content");
-    let input_parts: InputParts =
-        serde_json::from_slice(&input_content).expect("Parse the serde json");
+    let (input_parts, farm_job): (InputParts, Option<(RunnerClient, tokio::task::JoinHandle<()>)>) =
+        if let Some(driver_url) = args.driver_url.clone() {
+        // Farm mode: long-poll the driver instead of reading a single
+        // `--input` file, so many of these processes can chew through one
+        // queue concurrently with crash recovery.
+        let farm_secret = args
+            .farm_secret
+            .clone()
+            .expect("--farm-secret is required when --driver-url is set");
+        let runner = RunnerClient::new(driver_url, farm_secret, args.run_id.clone());
+        loop {
+            match runner.next_job().await {
+                Ok(Some(job)) => {
+                    eprintln!("farm::claimed_job::{}", &job.instance_id);
+                    let heartbeat = runner.spawn_heartbeat(job.instance_id.clone());
+                    // `job.instance` is the full payload the driver
+                    // enqueued (problem_statement, test patches, ...),
+                    // shipped straight over `/farm/claim` so a runner on a
+                    // different machine than whatever enqueued the job
+                    // doesn't need anything pre-staged on its local disk.
+                    //
+                    // `git_drname` isn't part of that payload: this runner
+                    // is expected to have already cloned `job.repo` at
+                    // `job.base_commit` into this conventional directory
+                    // before claiming work, the same way single-job
+                    // `--input` mode already assumes its `git_drname`
+                    // exists rather than cloning it itself.
+                    break (
+                        InputParts {
+                            git_drname: format!("{}-checkout", job.instance_id),
+                            instance: job.instance,
+                        },
+                        Some((runner, heartbeat)),
+                    );
+                }
+                Ok(None) => {
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!("farm::claim_failed::{:?}", err);
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            }
+        }
+    } else {
+        let input_path = args.input.clone().expect("--input is required when --driver-url is not set");
+        let input_content = tokio::fs::read(input_path).await.expect("path content");
+        (serde_json::from_slice(&input_content).expect("Parse the serde json"), None)
+    };
+
+    // This makes two runs that resolved differently actually diffable:
+    // what host/model/flags produced each trajectory.
+    sidecar::agentic::farm::env_info::EnvInfo::gather(
+        "anthropic".to_owned(),
+        llm_model_name,
+        args.max_depth,
+        args.midwit_mode,
+        args.json_mode,
+        input_parts.instance.environment_setup_commit.clone(),
+        input_parts.instance.version.clone(),
+    )
+    .write_to(&session_storage_dir)
+    .await
+    .unwrap_or_else(|err| eprintln!("agent::env_info::write_failed::{:?}", err));
 
     let cloned_session_id = args.run_id.to_string();
     let user_message = input_parts.instance.problem_statement.clone();
     let cloned_working_directory = input_parts.git_drname.to_owned();
+    let verification_working_directory = cloned_working_directory.clone();
     let tool_box = application.tool_box.clone();
     let llm_broker = application.llm_broker.clone();
 
+    // When a remote host is given, the checkout lives there rather than
+    // next to this process; `repo_ref` flips to `RepoRef::remote` and the
+    // exec backend dispatches bash/edit commands over SSH instead of
+    // touching the local filesystem. `tool_use_agentic`'s own bash/edit
+    // dispatch isn't in this checkout to thread this through, so it's used
+    // here for the commands this binary runs directly: capturing the
+    // agent's diff and verifying it.
+    let (repo_ref, exec_backend) = match (&args.remote_host, &args.remote_path) {
+        (Some(remote_host), Some(remote_path)) => {
+            let target = sidecar::agentic::farm::remote_exec::RemoteTarget::new(
+                remote_host.clone(),
+                remote_path.clone(),
+            );
+            (
+                RepoRef::remote(remote_host, remote_path).expect("remote repo_ref to work"),
+                sidecar::agentic::farm::remote_exec::ExecBackend::Ssh(target),
+            )
+        }
+        _ => (
+            RepoRef::local(&cloned_working_directory).expect("repo_ref to work"),
+            sidecar::agentic::farm::remote_exec::ExecBackend::Local(
+                std::path::PathBuf::from(&cloned_working_directory),
+            ),
+        ),
+    };
+
     let aide_rules = Some(format!(
         r#"- You have to complete the <instruction> provided by the user. You are an expert in {} and know the details of the repository.
 - You have access to a set of tools which you should use to complete the <instruction> 
@@ -189,6 +290,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.repo_name,
     ));
 
+    // Publish artifacts as the session progresses (the evolving diff every
+    // 30s, then the final patch) rather than only at exit. The sink is the
+    // local one, so they are written under `--log_directory/<instance_id>`
+    // on this worker.
+    let artifact_publisher = sidecar::agentic::farm::artifacts::ArtifactPublisher::new(
+        sidecar::agentic::farm::artifacts::ArtifactSink::Local {
+            log_directory: args.log_directory.clone(),
+        },
+        args.run_id.clone(),
+        input_parts.instance.instance_id.clone(),
+    );
+    let periodic_diff_upload = artifact_publisher.spawn_periodic_diff_upload(
+        std::path::PathBuf::from(&verification_working_directory),
+        std::time::Duration::from_secs(30),
+    );
+
     // wait for the agent to finish over here while busy looping
     println!("agent::tool_use::start");
     let _ = session_service
@@ -201,7 +318,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             vec![],
             "bash".to_owned(),
             vec![],
-            RepoRef::local(&cloned_working_directory).expect("repo_ref to work"),
+            repo_ref,
             cloned_working_directory,
             tool_box,
             llm_broker,
@@ -218,5 +335,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .await;
     println!("agent::tool_use::end");
+    periodic_diff_upload.abort();
+
+    // Capture the agent's final diff against base_commit and score it
+    // against the instance's own FAIL_TO_PASS/PASS_TO_PASS tests, so this
+    // run produces an immediately gradeable resolution rate. Both go
+    // through `exec_backend` so a remote checkout is diffed/verified over
+    // SSH instead of against this process's (empty) local filesystem.
+    let agent_patch = exec_backend
+        .run_bash(&format!("git diff {}", input_parts.instance.base_commit))
+        .await
+        .map(|(_code, stdout, _stderr)| stdout)
+        .unwrap_or_default();
+
+    let job_state = match sidecar::agentic::farm::verify::verify_instance(
+        input_parts.instance.instance_id.clone(),
+        &exec_backend,
+        agent_patch,
+        &input_parts.instance.test_patch,
+        &input_parts.instance.fail_to_pass,
+        &input_parts.instance.pass_to_pass,
+        args.log_directory.clone(),
+    )
+    .await
+    {
+        Ok(result) => {
+            println!("agent::verification::resolved::{}", result.resolved);
+            artifact_publisher
+                .publish(
+                    sidecar::agentic::farm::artifacts::ArtifactKind::FinalPatch,
+                    "final.patch",
+                    result.patch.clone(),
+                )
+                .await;
+            let _ = sidecar::agentic::farm::verify::append_result_to_report(
+                std::path::Path::new(&args.log_directory),
+                &result,
+            )
+            .await;
+            sidecar::agentic::farm::JobState::Finished
+        }
+        Err(err) => {
+            eprintln!("agent::verification::failed::{:?}", err);
+            sidecar::agentic::farm::JobState::Error
+        }
+    };
+
+    // In farm mode, stop the heartbeat and report the job to the driver.
+    if let Some((runner, heartbeat)) = farm_job {
+        heartbeat.abort();
+        if let Err(err) = runner
+            .resolve(input_parts.instance.instance_id.clone(), job_state)
+            .await
+        {
+            eprintln!("farm::resolve_failed::{:?}", err);
+        }
+    }
+
     Ok(())
 }
\ No newline at end of file
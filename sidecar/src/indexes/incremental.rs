@@ -0,0 +1,243 @@
+//! Salsa-style incremental recomputation for derived artifacts (a file's
+//! chunks, a chunk's embedding, a directory's aggregate index).
+//!
+//! A full re-embed on every repo change is wasteful in the same way a full
+//! rebuild is for a compiler: most derived values didn't actually change,
+//! only their inputs got touched. This models each derived value as a
+//! memoized query node keyed by its inputs, records which other nodes it
+//! read while computing, and uses the red-green revalidation algorithm (as
+//! in incremental compilers) to decide, node by node, whether a cached
+//! value can be reused or must be recomputed.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use sqlx::SqlitePool;
+
+/// Identifies a memoized derived value: a file's chunk list, a chunk's
+/// embedding, a directory's aggregate index, etc. Opaque and comparable so
+/// the graph doesn't need to know what kind of query produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct NodeId(pub String);
+
+impl NodeId {
+    pub fn file_chunks(relative_path: &str) -> Self {
+        Self(format!("file_chunks:{relative_path}"))
+    }
+
+    pub fn chunk_embedding(relative_path: &str, start_line: usize, end_line: usize) -> Self {
+        Self(format!(
+            "chunk_embedding:{relative_path}:{start_line}:{end_line}"
+        ))
+    }
+
+    pub fn directory_index(relative_path: &str) -> Self {
+        Self(format!("directory_index:{relative_path}"))
+    }
+}
+
+pub type Revision = u64;
+
+/// A node's colored state: green (verified unchanged or freshly recomputed,
+/// safe to reuse) or red (known to have changed, due for recomputation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeColor {
+    Green,
+    Red,
+}
+
+#[derive(Debug, Clone)]
+struct NodeEntry {
+    /// Hash of the node's last-computed output bytes, so a recompute that
+    /// produces byte-identical output doesn't force its dependents to
+    /// recompute too.
+    fingerprint: u64,
+    /// Revision at which this node was last verified or recomputed.
+    verified_at: Revision,
+    /// The other nodes this node's query read while computing, recorded so
+    /// `try_mark_green` knows what to revalidate before recomputing.
+    dependencies: Vec<NodeId>,
+    color: NodeColor,
+}
+
+/// The dependency graph of memoized query nodes plus the global revision
+/// counter. `persist`/`load` round-trip it through `db` so a restart only
+/// has to re-embed files whose content actually changed since the last run.
+pub struct IncrementalGraph {
+    revision: Mutex<Revision>,
+    nodes: Mutex<HashMap<NodeId, NodeEntry>>,
+}
+
+impl Default for IncrementalGraph {
+    fn default() -> Self {
+        Self {
+            revision: Mutex::new(0),
+            nodes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+fn fingerprint(output: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    output.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl IncrementalGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bumps the global revision and marks `node` dirty. Called when
+    /// `git`/`bg_poll` detects a changed file; everything downstream of
+    /// `node` is left unvalidated until something asks for it, at which
+    /// point `try_mark_green` walks the dependency chain from there.
+    pub fn mark_dirty(&self, node: &NodeId) -> Revision {
+        let mut revision = self.revision.lock().expect("revision lock poisoned");
+        *revision += 1;
+        let mut nodes = self.nodes.lock().expect("nodes lock poisoned");
+        if let Some(entry) = nodes.get_mut(node) {
+            entry.color = NodeColor::Red;
+        }
+        *revision
+    }
+
+    /// Attempts to validate `node` as still-green without recomputing it.
+    /// Recursively validates each recorded dependency first; if every
+    /// dependency re-validates to an unchanged fingerprint, `node` is
+    /// backdated to green and its cached value is reused. If any
+    /// dependency actually changed, `recompute_and_record_deps` runs,
+    /// `node`'s dependency edges are replaced with whatever it read this
+    /// time, and the new output fingerprint decides whether `node`'s own
+    /// dependents must be invalidated in turn.
+    ///
+    /// Returns whether `node` can be treated as green (cached value usable)
+    /// after this call.
+    pub fn try_mark_green(
+        &self,
+        node: &NodeId,
+        recompute_and_record_deps: &mut dyn FnMut(&NodeId) -> anyhow::Result<(Vec<u8>, Vec<NodeId>)>,
+    ) -> anyhow::Result<bool> {
+        let current_revision = *self.revision.lock().expect("revision lock poisoned");
+
+        let (already_green, dependencies) = {
+            let nodes = self.nodes.lock().expect("nodes lock poisoned");
+            match nodes.get(node) {
+                Some(entry) if entry.verified_at == current_revision => {
+                    return Ok(entry.color == NodeColor::Green);
+                }
+                Some(entry) => (entry.color == NodeColor::Green, entry.dependencies.clone()),
+                None => (false, Vec::new()),
+            }
+        };
+
+        // A node with no recorded dependencies (a root, or one we've never
+        // computed before) can't be revalidated transitively — it has to
+        // be recomputed once per revision, same as a salsa input query.
+        let mut all_dependencies_green = already_green && !dependencies.is_empty();
+        for dependency in &dependencies {
+            if !self.try_mark_green(dependency, recompute_and_record_deps)? {
+                all_dependencies_green = false;
+            }
+        }
+
+        if all_dependencies_green {
+            let mut nodes = self.nodes.lock().expect("nodes lock poisoned");
+            if let Some(entry) = nodes.get_mut(node) {
+                entry.verified_at = current_revision;
+                entry.color = NodeColor::Green;
+            }
+            return Ok(true);
+        }
+
+        let (output, new_dependencies) = recompute_and_record_deps(node)?;
+        let new_fingerprint = fingerprint(&output);
+
+        let mut nodes = self.nodes.lock().expect("nodes lock poisoned");
+        let output_unchanged = nodes
+            .get(node)
+            .map(|entry| entry.fingerprint == new_fingerprint)
+            .unwrap_or(false);
+
+        nodes.insert(
+            node.clone(),
+            NodeEntry {
+                fingerprint: new_fingerprint,
+                verified_at: current_revision,
+                dependencies: new_dependencies,
+                color: NodeColor::Green,
+            },
+        );
+
+        // Even though this node recomputed, its output can still be
+        // byte-identical (e.g. a reformatted comment that doesn't move
+        // chunk boundaries), which is what a caller uses to decide whether
+        // this node's own dependents need invalidating too.
+        Ok(output_unchanged)
+    }
+
+    pub async fn persist(&self, pool: &SqlitePool) -> anyhow::Result<()> {
+        let revision = *self.revision.lock().expect("revision lock poisoned") as i64;
+        let nodes = self.nodes.lock().expect("nodes lock poisoned").clone();
+        sqlx::query!("DELETE FROM incremental_graph_nodes")
+            .execute(pool)
+            .await?;
+        for (node_id, entry) in nodes.iter() {
+            let dependencies_json = serde_json::to_string(&entry.dependencies)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO incremental_graph_nodes
+                    (node_id, fingerprint, verified_at, dependencies_json)
+                VALUES (?, ?, ?, ?)
+                "#,
+                node_id.0,
+                entry.fingerprint as i64,
+                entry.verified_at as i64,
+                dependencies_json,
+            )
+            .execute(pool)
+            .await?;
+        }
+        sqlx::query!(
+            "INSERT INTO incremental_graph_revision (id, revision) VALUES (0, ?)
+             ON CONFLICT(id) DO UPDATE SET revision = excluded.revision",
+            revision,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load(pool: &SqlitePool) -> anyhow::Result<Self> {
+        let graph = Self::new();
+        if let Some(row) =
+            sqlx::query!("SELECT revision FROM incremental_graph_revision WHERE id = 0")
+                .fetch_optional(pool)
+                .await?
+        {
+            *graph.revision.lock().expect("revision lock poisoned") = row.revision as u64;
+        }
+
+        let rows = sqlx::query!(
+            "SELECT node_id, fingerprint, verified_at, dependencies_json FROM incremental_graph_nodes"
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut nodes = graph.nodes.lock().expect("nodes lock poisoned");
+        for row in rows {
+            let dependencies: Vec<NodeId> = serde_json::from_str(&row.dependencies_json)?;
+            nodes.insert(
+                NodeId(row.node_id),
+                NodeEntry {
+                    fingerprint: row.fingerprint as u64,
+                    verified_at: row.verified_at as u64,
+                    dependencies,
+                    color: NodeColor::Green,
+                },
+            );
+        }
+        drop(nodes);
+        Ok(graph)
+    }
+}
@@ -0,0 +1,6 @@
+//! Derived-artifact indexes over a repo's contents (chunks, embeddings,
+//! symbol tables), and the machinery that keeps them in sync with the repo
+//! without redoing work that's already up to date.
+
+pub mod incremental;
+pub mod symbols;
@@ -0,0 +1,122 @@
+//! Symbol-name index complementing `semantic_search`'s vector lookup:
+//! exact, prefix, and bounded-edit-distance fuzzy search over every
+//! function/type/const identifier extracted during chunking, built on a
+//! finite-state transducer. The FST is immutable and mmap-able, so exact
+//! and prefix hits cost microseconds and never need an embedding
+//! round-trip — useful for "go to symbol" and for `agent` resolving a
+//! mentioned identifier to a concrete location before reasoning about it.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use fst::automaton::Str;
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
+
+/// Where a symbol occurrence came from.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Posting {
+    pub file_id: String,
+    pub byte_range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    Exact,
+    /// Enumerate everything starting with `query`, walking the FST from
+    /// the prefix's node instead of scanning every key.
+    Prefix,
+    /// Intersect the FST with a Levenshtein automaton of the given edit
+    /// distance (1-2 is the useful range; higher fans out too much).
+    Fuzzy { max_edits: u8 },
+}
+
+/// Wraps a `levenshtein_automata::DFA` so it can drive an `fst` stream —
+/// the two crates don't implement each other's traits, so this adapter is
+/// the usual way the pairing gets wired up.
+struct LevenshteinDfa(DFA);
+
+impl Automaton for LevenshteinDfa {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(
+            self.0.distance(*state),
+            levenshtein_automata::Distance::Exact(_)
+        )
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != levenshtein_automata::SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// An immutable symbol-name -> posting-list index. `fst::Map` only stores
+/// a single `u64` per key, so each symbol string maps to an id into
+/// `postings`, which carries the (possibly multi-occurrence) posting list
+/// for that name.
+pub struct SymbolIndex {
+    map: Map<Vec<u8>>,
+    postings: Vec<Vec<Posting>>,
+}
+
+impl SymbolIndex {
+    /// Builds the index from every symbol occurrence collected during
+    /// chunking. `MapBuilder` requires sorted, deduplicated keys, so
+    /// occurrences are grouped by symbol name before insertion.
+    pub fn build(occurrences: Vec<(String, Posting)>) -> anyhow::Result<Self> {
+        let mut grouped: BTreeMap<String, Vec<Posting>> = BTreeMap::new();
+        for (symbol, posting) in occurrences {
+            grouped.entry(symbol).or_default().push(posting);
+        }
+
+        let mut builder = MapBuilder::memory();
+        let mut postings = Vec::with_capacity(grouped.len());
+        for (id, (symbol, symbol_postings)) in grouped.into_iter().enumerate() {
+            builder.insert(symbol, id as u64)?;
+            postings.push(symbol_postings);
+        }
+
+        let map = Map::new(builder.into_inner()?)?;
+        Ok(Self { map, postings })
+    }
+
+    pub fn search(&self, query: &str, kind: SearchKind) -> Vec<&Posting> {
+        match kind {
+            SearchKind::Exact => self
+                .map
+                .get(query)
+                .map(|id| self.postings[id as usize].iter().collect())
+                .unwrap_or_default(),
+            SearchKind::Prefix => {
+                let automaton = Str::new(query).starts_with();
+                self.collect_matches(self.map.search(automaton))
+            }
+            SearchKind::Fuzzy { max_edits } => {
+                let builder = LevenshteinAutomatonBuilder::new(max_edits, true);
+                let dfa = LevenshteinDfa(builder.build_dfa(query));
+                self.collect_matches(self.map.search(dfa))
+            }
+        }
+    }
+
+    fn collect_matches<'a, A: Automaton>(
+        &'a self,
+        stream_builder: fst::map::StreamBuilder<'a, A>,
+    ) -> Vec<&'a Posting> {
+        let mut results = Vec::new();
+        let mut stream = stream_builder.into_stream();
+        while let Some((_symbol, id)) = stream.next() {
+            results.extend(self.postings[id as usize].iter());
+        }
+        results
+    }
+}
@@ -0,0 +1,165 @@
+//! Workspace/crate-graph awareness, so a multi-crate checkout is indexed
+//! as the dependency graph it actually is instead of one flat file tree.
+//!
+//! Without this, `indexes`/`semantic_search` treat every file the same
+//! regardless of which crate owns it, so a query from inside crate A can't
+//! be scoped to "A and its transitive dependencies," and ranking can't
+//! prefer symbols actually reachable from where the user is editing.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Identifies a crate within a workspace. Opaque so resolvers for other
+/// ecosystems (not just Cargo) can mint their own ids without the graph
+/// caring how they're derived.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct CrateId(pub String);
+
+#[derive(Debug, Clone)]
+pub struct CrateNode {
+    pub id: CrateId,
+    pub name: String,
+    pub root_path: PathBuf,
+    /// Crates this one directly depends on.
+    pub dependencies: Vec<CrateId>,
+}
+
+/// The dependency graph for a workspace: one node per crate plus a
+/// file -> owning-crate index, so `indexes`/`semantic_search` can tag every
+/// result with the `CrateId` it came from.
+#[derive(Debug, Clone, Default)]
+pub struct CrateGraph {
+    crates: HashMap<CrateId, CrateNode>,
+    /// Sorted by `root_path` length descending so the longest (most
+    /// specific) matching prefix wins when a file sits under nested crate
+    /// roots.
+    file_owners: Vec<(PathBuf, CrateId)>,
+}
+
+impl CrateGraph {
+    pub fn crate_by_id(&self, id: &CrateId) -> Option<&CrateNode> {
+        self.crates.get(id)
+    }
+
+    /// The crate that owns `file_path`, if any crate root is a prefix of
+    /// it.
+    pub fn owning_crate(&self, file_path: &Path) -> Option<&CrateId> {
+        self.file_owners
+            .iter()
+            .find(|(root, _)| file_path.starts_with(root))
+            .map(|(_, id)| id)
+    }
+
+    /// `id` plus every crate reachable by following `dependencies`
+    /// transitively, for scoping a search to "this crate and what it
+    /// depends on."
+    pub fn transitive_dependencies(&self, id: &CrateId) -> Vec<CrateId> {
+        let mut seen = std::collections::HashSet::new();
+        let mut stack = vec![id.clone()];
+        let mut result = Vec::new();
+        while let Some(current) = stack.pop() {
+            if !seen.insert(current.clone()) {
+                continue;
+            }
+            result.push(current.clone());
+            if let Some(node) = self.crates.get(&current) {
+                stack.extend(node.dependencies.iter().cloned());
+            }
+        }
+        result
+    }
+}
+
+/// Builds a `CrateId`/`CrateGraph` for an ecosystem by reading its
+/// manifests from disk. Cargo is the only implementation today; other
+/// ecosystems (npm workspaces, a Go module graph) plug in by implementing
+/// this against their own manifest format.
+pub trait WorkspaceResolver {
+    fn resolve(&self, workspace_root: &Path) -> anyhow::Result<CrateGraph>;
+}
+
+/// Reads `Cargo.toml` workspace manifests: the root `[workspace]` member
+/// list, and each member's own `[package]`/`[dependencies]` tables.
+pub struct CargoWorkspaceResolver;
+
+impl WorkspaceResolver for CargoWorkspaceResolver {
+    fn resolve(&self, workspace_root: &Path) -> anyhow::Result<CrateGraph> {
+        let root_manifest = std::fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+        let root_value: toml::Value = root_manifest.parse()?;
+
+        let member_globs = root_value
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .and_then(|members| members.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut member_dirs = Vec::new();
+        for member_glob in member_globs {
+            let pattern = member_glob
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("workspace member entry is not a string"))?;
+            for entry in glob::glob(&workspace_root.join(pattern).to_string_lossy())? {
+                member_dirs.push(entry?);
+            }
+        }
+        // A single-crate checkout with no `[workspace]` table is its own
+        // sole member.
+        if member_dirs.is_empty() {
+            member_dirs.push(workspace_root.to_path_buf());
+        }
+
+        let mut crates = HashMap::new();
+        let mut name_to_id = HashMap::new();
+        let mut manifests = Vec::new();
+
+        for member_dir in &member_dirs {
+            let manifest_path = member_dir.join("Cargo.toml");
+            let Ok(manifest_contents) = std::fs::read_to_string(&manifest_path) else {
+                continue;
+            };
+            let manifest: toml::Value = manifest_contents.parse()?;
+            let Some(name) = manifest
+                .get("package")
+                .and_then(|package| package.get("name"))
+                .and_then(|name| name.as_str())
+            else {
+                continue;
+            };
+            let id = CrateId(name.to_owned());
+            name_to_id.insert(name.to_owned(), id.clone());
+            manifests.push((id, name.to_owned(), member_dir.clone(), manifest));
+        }
+
+        for (id, name, root_path, manifest) in manifests {
+            let dependencies = manifest
+                .get("dependencies")
+                .and_then(|deps| deps.as_table())
+                .map(|deps| {
+                    deps.keys()
+                        .filter_map(|dep_name| name_to_id.get(dep_name).cloned())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            crates.insert(
+                id.clone(),
+                CrateNode {
+                    id,
+                    name,
+                    root_path,
+                    dependencies,
+                },
+            );
+        }
+
+        let mut file_owners: Vec<(PathBuf, CrateId)> =
+            crates.values().map(|node| (node.root_path.clone(), node.id.clone())).collect();
+        file_owners.sort_by_key(|(root, _)| std::cmp::Reverse(root.as_os_str().len()));
+
+        Ok(CrateGraph {
+            crates,
+            file_owners,
+        })
+    }
+}
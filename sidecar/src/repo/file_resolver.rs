@@ -0,0 +1,176 @@
+//! Maps opaque `FileId`s to concrete file contents, so `chunking`,
+//! `indexes`, and `semantic_search` never have to touch `std::path`
+//! filesystem access directly. Mirrors the real-to-virtual path mapping a
+//! media server uses to hide physical layout: callers only ever see
+//! `FileId` + a relative path, and a `Vfs` implementation decides where the
+//! bytes actually come from.
+//!
+//! This is what lets the crate index an unbuilt remote ref or an editor's
+//! unsaved buffers the same way it indexes a normal checkout, and it gives
+//! `indexes::incremental::NodeId` a stable key that doesn't depend on
+//! whether the file is actually on disk right now.
+//!
+//! `DiskVfs` is constructed by `webserver::analysis::for_file`; nothing in
+//! this checkout constructs a `GitTreeVfs`. `IncrementalGraph`'s
+//! `recompute_and_record_deps` closure, which reads a file's bytes to
+//! recompute its `file_chunks` node, is supplied by whoever drives
+//! chunking, and there's no such driver in this checkout (no `chunking`
+//! module at all), so neither `Vfs` is used from there.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::repo::types::RepoRef;
+
+/// Opaque handle to a file as seen by a `Vfs`. Two `FileId`s compare equal
+/// only if they came from the same resolver for the same repo — callers
+/// should never try to derive a filesystem path from one directly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct FileId(pub String);
+
+/// Resolves `FileId`s to relative paths and back, without exposing how a
+/// given implementation actually stores file content.
+pub trait FileResolver {
+    /// The relative path a `FileId` was minted from, for display and for
+    /// crate/ownership lookups that still key off paths (e.g. `CrateGraph`).
+    fn file_stem(&self, file: &FileId) -> Option<&Path>;
+
+    /// Looks up the `FileId` for `relative_path`, if this resolver knows
+    /// about it.
+    fn resolve(&self, relative_path: &Path) -> Option<FileId>;
+}
+
+/// Reads file content through a `FileResolver`'s `FileId`s rather than raw
+/// paths, so `chunking`/`indexes`/`semantic_search` stay agnostic to
+/// whether they're reading a checkout, an unbuilt ref, or an editor buffer.
+#[async_trait]
+pub trait Vfs: FileResolver {
+    async fn read(&self, file: &FileId) -> anyhow::Result<Vec<u8>>;
+}
+
+/// The current on-disk checkout: `FileId`s are just the relative path,
+/// reads go straight through `tokio::fs`.
+pub struct DiskVfs {
+    root: PathBuf,
+    known: HashMap<PathBuf, FileId>,
+}
+
+impl DiskVfs {
+    /// `known_relative_paths` is the set of files this resolver should be
+    /// able to answer `resolve` for — typically the output of a repo walk
+    /// done once up front, so `resolve` doesn't need to hit the filesystem.
+    pub fn new(root: PathBuf, known_relative_paths: Vec<PathBuf>) -> Self {
+        let known = known_relative_paths
+            .into_iter()
+            .map(|relative_path| {
+                let id = FileId(relative_path.to_string_lossy().into_owned());
+                (relative_path, id)
+            })
+            .collect();
+        Self { root, known }
+    }
+}
+
+impl FileResolver for DiskVfs {
+    fn file_stem(&self, file: &FileId) -> Option<&Path> {
+        self.known
+            .iter()
+            .find(|(_, id)| *id == file)
+            .map(|(relative_path, _)| relative_path.as_path())
+    }
+
+    fn resolve(&self, relative_path: &Path) -> Option<FileId> {
+        self.known.get(relative_path).cloned()
+    }
+}
+
+#[async_trait]
+impl Vfs for DiskVfs {
+    async fn read(&self, file: &FileId) -> anyhow::Result<Vec<u8>> {
+        let relative_path = self
+            .file_stem(file)
+            .ok_or_else(|| anyhow::anyhow!("unknown file id: {:?}", file))?;
+        Ok(tokio::fs::read(self.root.join(relative_path)).await?)
+    }
+}
+
+/// Reads blob contents out of a specific `git` commit/tree without
+/// checking it out, via `git show <commit>:<path>` — so an unbuilt remote
+/// ref can be indexed without materializing it on disk first.
+pub struct GitTreeVfs {
+    repo_root: PathBuf,
+    reporef: RepoRef,
+    commit: String,
+    known: HashMap<PathBuf, FileId>,
+}
+
+impl GitTreeVfs {
+    /// `relative_paths` is the file listing for `commit`, typically from
+    /// `git ls-tree -r --name-only <commit>`.
+    pub fn new(
+        repo_root: PathBuf,
+        reporef: RepoRef,
+        commit: String,
+        relative_paths: Vec<PathBuf>,
+    ) -> Self {
+        let known = relative_paths
+            .into_iter()
+            .map(|relative_path| {
+                // Scope the id to the commit so the same path at two
+                // different refs never collides in a shared `FileId` space.
+                let id = FileId(format!("{commit}:{}", relative_path.to_string_lossy()));
+                (relative_path, id)
+            })
+            .collect();
+        Self {
+            repo_root,
+            reporef,
+            commit,
+            known,
+        }
+    }
+
+    pub fn reporef(&self) -> &RepoRef {
+        &self.reporef
+    }
+}
+
+impl FileResolver for GitTreeVfs {
+    fn file_stem(&self, file: &FileId) -> Option<&Path> {
+        self.known
+            .iter()
+            .find(|(_, id)| *id == file)
+            .map(|(relative_path, _)| relative_path.as_path())
+    }
+
+    fn resolve(&self, relative_path: &Path) -> Option<FileId> {
+        self.known.get(relative_path).cloned()
+    }
+}
+
+#[async_trait]
+impl Vfs for GitTreeVfs {
+    async fn read(&self, file: &FileId) -> anyhow::Result<Vec<u8>> {
+        let relative_path = self
+            .file_stem(file)
+            .ok_or_else(|| anyhow::anyhow!("unknown file id: {:?}", file))?;
+        let spec = format!("{}:{}", self.commit, relative_path.to_string_lossy());
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_root)
+            .arg("show")
+            .arg(&spec)
+            .output()
+            .await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git show {} failed: {}",
+                spec,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(output.stdout)
+    }
+}
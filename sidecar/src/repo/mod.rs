@@ -0,0 +1,7 @@
+//! Repo-level structure that search and indexing reason about independently
+//! of any single file: the crate dependency graph (`crate_graph`) and the
+//! abstraction over reading file contents from disk or a git tree
+//! (`file_resolver`).
+
+pub mod crate_graph;
+pub mod file_resolver;
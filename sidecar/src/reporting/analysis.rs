@@ -0,0 +1,181 @@
+//! IDE-consumable analysis export: definitions, references, and the
+//! relationships between them, all tagged with [`Span`] rather than raw
+//! byte offsets so a caller can't accidentally mix line/column coordinates
+//! with byte ranges. Shared as plain serde structs so `agent` can use them
+//! as cheap owned values in-process while `webserver` serializes the exact
+//! same types to JSON for an external editor — no separate wire format to
+//! keep in sync.
+
+use dashmap::DashMap;
+
+use crate::repo::file_resolver::FileId;
+
+/// A 1-based line/column position, matching how editors report cursor
+/// position rather than a raw byte offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A range within a specific file, in editor coordinates. Distinct from the
+/// byte-range `Posting`s used by `indexes::symbols` — this type exists
+/// precisely so the two coordinate systems can't be confused at a call
+/// site.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Span {
+    pub file_id: FileId,
+    pub start: LineCol,
+    pub end: LineCol,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Definition {
+    pub symbol: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Reference {
+    pub symbol: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipKind {
+    Calls,
+    Implements,
+    Extends,
+}
+
+/// A directed edge between two spans, e.g. a call site pointing at the
+/// definition it calls.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Relationship {
+    pub kind: RelationshipKind,
+    pub from: Span,
+    pub to: Span,
+}
+
+/// Everything known about one file: what it defines, what it references,
+/// and how those relate to definitions elsewhere.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FileAnalysis {
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+    pub relationships: Vec<Relationship>,
+}
+
+/// The handful of keywords `naive_scan_definitions` recognizes at the start
+/// of a trimmed line, ordered so a longer prefix (`pub fn `) is tried before
+/// the shorter one it contains (`fn `).
+const DEFINITION_KEYWORDS: &[&str] = &["pub fn ", "fn ", "struct ", "class ", "def ", "function "];
+
+/// A single-pass, line-oriented stand-in for the tree-sitter-driven
+/// extraction pass `AnalysisIndex`'s doc comment describes: no such pass
+/// exists in this checkout (there's no `chunking` module at all for it to
+/// live in), so this recognizes a definition only when it's the first thing
+/// on its own (trimmed) line — `pub fn name(`, `struct Name`, `def name(`,
+/// etc. It doesn't understand nesting or syntax, finds no references or
+/// relationships, and will miss or misparse anything shaped differently
+/// (a one-line closure assigned to a name, a macro-generated definition,
+/// multi-line generics before the name). It exists so `for_file` returns
+/// something real for a real file instead of always being empty, not as a
+/// substitute for actually parsing the language.
+fn naive_scan_definitions(file_id: &FileId, content: &str) -> Vec<Definition> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line_idx, line)| {
+            let trimmed = line.trim_start();
+            let keyword = DEFINITION_KEYWORDS
+                .iter()
+                .find(|keyword| trimmed.starts_with(**keyword))?;
+            let rest = &trimmed[keyword.len()..];
+            let symbol: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if symbol.is_empty() {
+                return None;
+            }
+
+            let indent = line.len() - trimmed.len();
+            let start_column = indent + keyword.len() + 1;
+            let line_number = line_idx + 1;
+            Some(Definition {
+                symbol: symbol.clone(),
+                span: Span {
+                    file_id: file_id.clone(),
+                    start: LineCol {
+                        line: line_number,
+                        column: start_column,
+                    },
+                    end: LineCol {
+                        line: line_number,
+                        column: start_column + symbol.len(),
+                    },
+                },
+            })
+        })
+        .collect()
+}
+
+/// Holds the analysis data for every indexed file, queried per-file so
+/// `agent` and `webserver` can build go-to-definition and find-all-references
+/// without re-scanning the whole repo.
+///
+/// `record_definition`/`record_reference`/`record_relationship` have no
+/// caller in this checkout; `populate_naive` (see its doc comment for what
+/// that stopgap can and can't find) writes its scan straight into
+/// `by_file`. `by_file` is a `DashMap` rather than a plain `HashMap` so
+/// `populate_naive` can run from a shared `Arc<AnalysisIndex>` (the shape
+/// `webserver::analysis::for_file` already receives it in) without needing
+/// a `Mutex` around the whole index.
+#[derive(Debug, Default)]
+pub struct AnalysisIndex {
+    by_file: DashMap<FileId, FileAnalysis>,
+}
+
+impl AnalysisIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_definition(&self, file: FileId, definition: Definition) {
+        self.by_file.entry(file).or_default().definitions.push(definition);
+    }
+
+    pub fn record_reference(&self, file: FileId, reference: Reference) {
+        self.by_file.entry(file).or_default().references.push(reference);
+    }
+
+    pub fn record_relationship(&self, file: FileId, relationship: Relationship) {
+        self.by_file
+            .entry(file)
+            .or_default()
+            .relationships
+            .push(relationship);
+    }
+
+    /// Runs `naive_scan_definitions` over `content` and records whatever it
+    /// finds for `file`, replacing any previous scan of the same file.
+    pub fn populate_naive(&self, file: FileId, content: &str) {
+        let definitions = naive_scan_definitions(&file, content);
+        self.by_file.insert(
+            file,
+            FileAnalysis {
+                definitions,
+                references: vec![],
+                relationships: vec![],
+            },
+        );
+    }
+
+    /// All defs/refs/relationships recorded for `file`, or an empty
+    /// `FileAnalysis` if nothing has been indexed for it yet.
+    pub fn for_file(&self, file: &FileId) -> FileAnalysis {
+        self.by_file.get(file).map(|entry| entry.clone()).unwrap_or_default()
+    }
+}
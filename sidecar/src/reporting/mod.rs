@@ -0,0 +1,5 @@
+//! Structured, typed exports of what indexing already knows about a file —
+//! currently just code-analysis spans (`analysis`) — for consumers that
+//! want more than a blob of text.
+
+pub mod analysis;
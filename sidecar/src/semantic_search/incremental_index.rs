@@ -0,0 +1,228 @@
+//! Persistent, git-aware embedding index backing hybrid/semantic search.
+//!
+//! Reindexing a large repo from scratch on every query is wasteful: most
+//! files haven't changed since the last index. This keys each chunk's
+//! embedding by the git blob hash of the file it came from, so a reindex
+//! only has to touch files whose blob hash moved between the indexed
+//! commit and HEAD.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use sqlx::SqlitePool;
+
+use crate::repo::types::RepoRef;
+
+#[derive(Debug, Clone)]
+pub struct IndexedChunk {
+    pub blob_hash: String,
+    pub relative_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub embedding: Vec<f32>,
+}
+
+/// One file as seen by `git`: its path and the blob hash of its current
+/// content, used to decide whether we need to re-embed it.
+#[derive(Debug, Clone)]
+pub struct RepoFileBlob {
+    pub relative_path: String,
+    pub blob_hash: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ReindexPlan {
+    /// Blob hashes present on disk but missing (or stale) in the index.
+    pub changed_or_new: Vec<RepoFileBlob>,
+    /// Blob hashes present in the index but no longer present on disk.
+    pub removed_blob_hashes: Vec<String>,
+}
+
+/// Diffs the blob hashes we have stored for `reporef` against what `git`
+/// reports for the current tree, so we only embed what actually changed.
+pub fn plan_reindex(current_files: &[RepoFileBlob], indexed_blob_hashes: &[String]) -> ReindexPlan {
+    let indexed: HashSet<&str> = indexed_blob_hashes.iter().map(String::as_str).collect();
+    let current: HashSet<&str> = current_files.iter().map(|file| file.blob_hash.as_str()).collect();
+
+    let changed_or_new = current_files
+        .iter()
+        .filter(|file| !indexed.contains(file.blob_hash.as_str()))
+        .cloned()
+        .collect();
+
+    let removed_blob_hashes = indexed_blob_hashes
+        .iter()
+        .filter(|blob_hash| !current.contains(blob_hash.as_str()))
+        .cloned()
+        .collect();
+
+    ReindexPlan {
+        changed_or_new,
+        removed_blob_hashes,
+    }
+}
+
+/// Lists every file `git` tracks at `repo_root`'s `HEAD`, blob hash
+/// included, via `git ls-tree -r HEAD`. This is the `current_files` side of
+/// `plan_reindex`/`refresh_after_pull`: the committed tree at `HEAD`, to
+/// diff against what's already indexed. Uncommitted edits in the working
+/// tree are not seen.
+pub async fn list_repo_file_blobs(repo_root: &Path) -> anyhow::Result<Vec<RepoFileBlob>> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .arg("ls-tree")
+        .arg("-r")
+        .arg("HEAD")
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git ls-tree failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            // Each line is `<mode> blob <hash>\t<path>`.
+            let (meta, relative_path) = line.split_once('\t')?;
+            let blob_hash = meta.split_whitespace().nth(2)?.to_owned();
+            Some(RepoFileBlob {
+                relative_path: relative_path.to_owned(),
+                blob_hash,
+            })
+        })
+        .collect())
+}
+
+/// Persists chunk embeddings for `reporef`, keyed by git blob hash, so a
+/// restart only has to re-embed files whose blob hash actually changed.
+pub struct SemanticIndexStore {
+    pool: SqlitePool,
+}
+
+impl SemanticIndexStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates `semantic_chunks` if this is the first run against `pool`.
+    pub async fn run_migrations(pool: &SqlitePool) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS semantic_chunks (
+                reporef TEXT NOT NULL,
+                blob_hash TEXT NOT NULL,
+                relative_path TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                embedding BLOB NOT NULL,
+                PRIMARY KEY (reporef, blob_hash, start_line, end_line)
+            )",
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn indexed_blob_hashes(&self, reporef: &RepoRef) -> anyhow::Result<Vec<String>> {
+        let reporef_str = reporef.to_string();
+        let rows = sqlx::query!(
+            "SELECT DISTINCT blob_hash FROM semantic_chunks WHERE reporef = ?",
+            reporef_str,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|row| row.blob_hash).collect())
+    }
+
+    pub async fn delete_blob(&self, reporef: &RepoRef, blob_hash: &str) -> anyhow::Result<()> {
+        let reporef_str = reporef.to_string();
+        sqlx::query!(
+            "DELETE FROM semantic_chunks WHERE reporef = ? AND blob_hash = ?",
+            reporef_str,
+            blob_hash,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn upsert_chunks(
+        &self,
+        reporef: &RepoRef,
+        chunks: &[IndexedChunk],
+    ) -> anyhow::Result<()> {
+        let reporef_str = reporef.to_string();
+        for chunk in chunks {
+            let embedding_bytes = bincode::serialize(&chunk.embedding)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO semantic_chunks
+                    (reporef, blob_hash, relative_path, start_line, end_line, embedding)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT(reporef, blob_hash, start_line, end_line)
+                DO UPDATE SET embedding = excluded.embedding
+                "#,
+                reporef_str,
+                chunk.blob_hash,
+                chunk.relative_path,
+                chunk.start_line as i64,
+                chunk.end_line as i64,
+                embedding_bytes,
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Query-time lookup, filtered down to the repo the caller is searching
+    /// over so results from other indexed repos never leak in.
+    pub async fn chunks_for_repo(&self, reporef: &RepoRef) -> anyhow::Result<Vec<IndexedChunk>> {
+        let reporef_str = reporef.to_string();
+        let rows = sqlx::query!(
+            "SELECT blob_hash, relative_path, start_line, end_line, embedding FROM semantic_chunks WHERE reporef = ?",
+            reporef_str,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(IndexedChunk {
+                    blob_hash: row.blob_hash,
+                    relative_path: row.relative_path,
+                    start_line: row.start_line as usize,
+                    end_line: row.end_line as usize,
+                    embedding: bincode::deserialize(&row.embedding)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Hook meant to be called after a `git pull`/branch switch so the semantic
+/// half of hybrid search stays current without forcing a full re-embed.
+pub async fn refresh_after_pull(
+    store: &SemanticIndexStore,
+    reporef: &RepoRef,
+    current_files: Vec<RepoFileBlob>,
+    embed_batch: impl Fn(Vec<RepoFileBlob>) -> anyhow::Result<Vec<IndexedChunk>>,
+) -> anyhow::Result<()> {
+    let indexed_blob_hashes = store.indexed_blob_hashes(reporef).await?;
+    let plan = plan_reindex(&current_files, &indexed_blob_hashes);
+
+    for blob_hash in &plan.removed_blob_hashes {
+        store.delete_blob(reporef, blob_hash).await?;
+    }
+
+    if !plan.changed_or_new.is_empty() {
+        let chunks = embed_batch(plan.changed_or_new)?;
+        store.upsert_chunks(reporef, &chunks).await?;
+    }
+
+    Ok(())
+}
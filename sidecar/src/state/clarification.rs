@@ -0,0 +1,55 @@
+//! Holds the in-flight clarification question for a task, if any, so an
+//! agent run can suspend on `ask` and a later `webserver` request can
+//! resume it with `answer` — without the agent loop and the HTTP handler
+//! needing a direct reference to each other.
+//!
+//! Keyed by task id the same way `webserver::plan_ot`'s operation history
+//! is keyed by plan id: a global registry behind a `DashMap`, since both
+//! sides (the agent loop and the webserver handler that receives the
+//! `Answer`) only ever have the id in hand, not a shared handle.
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::agentic::tool::human::qa::{Answer, Question};
+
+static PENDING_QUESTIONS: Lazy<DashMap<Uuid, PendingQuestion>> = Lazy::new(DashMap::new);
+
+struct PendingQuestion {
+    question: Question,
+    answer_tx: oneshot::Sender<Answer>,
+}
+
+/// Suspends `task_id` on `question`: records it as pending and returns a
+/// receiver that resolves once `answer` is called with a matching
+/// `task_id`. The caller (the agent loop) should `.await` the receiver
+/// instead of guessing at intent.
+pub fn ask(task_id: Uuid, question: Question) -> oneshot::Receiver<Answer> {
+    let (answer_tx, answer_rx) = oneshot::channel();
+    PENDING_QUESTIONS.insert(task_id, PendingQuestion { question, answer_tx });
+    answer_rx
+}
+
+/// The question `task_id` is currently blocked on, if any, for `webserver`
+/// to surface over SSE.
+pub fn pending(task_id: Uuid) -> Option<Question> {
+    PENDING_QUESTIONS
+        .get(&task_id)
+        .map(|entry| entry.question.clone())
+}
+
+/// Resolves `task_id`'s pending question with `answer`, waking the
+/// suspended agent run. Errors if there's no pending question for
+/// `task_id`, or if the agent run that was waiting on it has since been
+/// dropped.
+pub fn answer(task_id: Uuid, answer: Answer) -> anyhow::Result<()> {
+    let (_, pending) = PENDING_QUESTIONS
+        .remove(&task_id)
+        .ok_or_else(|| anyhow::anyhow!("no pending question for task {task_id}"))?;
+    pending
+        .answer_tx
+        .send(answer)
+        .map_err(|_| anyhow::anyhow!("agent run for task {task_id} is no longer waiting"))
+}
@@ -0,0 +1,5 @@
+//! Per-session state that outlives a single request, starting with the
+//! pending-clarification slot an agent run blocks on when it needs human
+//! input mid-task (see `agent::clarification`).
+
+pub mod clarification;
@@ -1,5 +1,5 @@
 use super::agent_stream::generate_agent_stream;
-use super::model_selection::LLMClientConfig;
+use super::model_selection::{agent_action_tools, LLMClientConfig, ToolSchema};
 use super::types::json;
 use anyhow::Context;
 use std::collections::HashSet;
@@ -15,6 +15,7 @@ use crate::agent::types::ConversationMessage;
 use crate::agent::types::{Agent, VariableInformation as AgentVariableInformation};
 use crate::application::application::Application;
 use crate::chunking::text_document::Position as DocumentPosition;
+use crate::repo::crate_graph::{CargoWorkspaceResolver, CrateGraph, WorkspaceResolver};
 use crate::repo::types::RepoRef;
 use crate::reporting::posthog::client::PosthogEvent;
 
@@ -25,6 +26,61 @@ fn default_thread_id() -> uuid::Uuid {
     uuid::Uuid::new_v4()
 }
 
+/// The tool schemas an `Agent` should be handed for this request: only
+/// providers that advertised `supports_tool_calling` get them, since
+/// everyone else is still going through the free-form text-parsing path
+/// `tool_call_to_agent_action` was added to replace.
+fn tools_for(model_config: &LLMClientConfig) -> Vec<ToolSchema> {
+    if model_config.supports_tool_calling {
+        agent_action_tools()
+    } else {
+        vec![]
+    }
+}
+
+/// Keeps `store` current for `reporef` before `hybrid_search`'s semantic
+/// retriever runs, the same way its lexical/git-log retrievers already
+/// work off the live tree rather than a stale snapshot. Only runs when the
+/// caller supplied `workspace_root` (diffing against the tree needs a real
+/// checkout path to run `git ls-tree` against); a failure here — including
+/// the expected one while no embedding model is wired into this checkout,
+/// so a changed file can't actually be re-embedded yet — is logged and
+/// swallowed rather than failing the whole search, so `hybrid_search`
+/// degrades to whatever was already indexed instead of 500ing.
+async fn refresh_semantic_index(
+    store: &crate::semantic_search::incremental_index::SemanticIndexStore,
+    reporef: &RepoRef,
+    workspace_root: &str,
+) {
+    let current_files = match crate::semantic_search::incremental_index::list_repo_file_blobs(
+        std::path::Path::new(workspace_root),
+    )
+    .await
+    {
+        Ok(current_files) => current_files,
+        Err(err) => {
+            tracing::warn!(%err, "failed to list repo files for semantic index refresh");
+            return;
+        }
+    };
+
+    let result = crate::semantic_search::incremental_index::refresh_after_pull(
+        store,
+        reporef,
+        current_files,
+        |files| {
+            anyhow::bail!(
+                "{} file(s) need (re-)embedding but no embedder is wired into this checkout yet",
+                files.len()
+            )
+        },
+    )
+    .await;
+    if let Err(err) = result {
+        tracing::warn!(%err, "semantic index refresh incomplete");
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SearchInformation {
     pub query: String,
@@ -32,6 +88,8 @@ pub struct SearchInformation {
     #[serde(default = "default_thread_id")]
     pub thread_id: uuid::Uuid,
     pub model_config: LLMClientConfig,
+    #[serde(default)]
+    pub provider_key_override: Option<crate::webserver::model_selection::ProviderKeyOverride>,
 }
 
 impl ApiResponse for SearchInformation {}
@@ -55,9 +113,11 @@ pub async fn search_agent(
         reporef,
         thread_id,
         model_config,
+        provider_key_override,
     }): axumQuery<SearchInformation>,
     Extension(app): Extension<Application>,
 ) -> Result<impl IntoResponse> {
+    let model_config = model_config.with_override(provider_key_override);
     let reranker = app.reranker.clone();
     let chat_broker = app.chat_broker.clone();
     let llm_tokenizer = app.llm_tokenizer.clone();
@@ -70,6 +130,7 @@ pub async fn search_agent(
         ConversationMessage::load_from_db(sql_db.clone(), &reporef, thread_id)
             .await
             .expect("loading from db to never fail");
+    let tools = tools_for(&model_config);
     let agent = Agent::prepare_for_search(
         app,
         reporef,
@@ -85,11 +146,16 @@ pub async fn search_agent(
         llm_tokenizer,
         chat_broker,
         reranker,
+        tools,
     );
 
     generate_agent_stream(agent, action, receiver).await
 }
 
+fn default_rrf_k() -> f32 {
+    60.0
+}
+
 // Here we are going to provide a hybrid search index which combines both the
 // lexical and the semantic search together
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -97,6 +163,27 @@ pub struct HybridSearchQuery {
     query: String,
     repo: RepoRef,
     model_config: LLMClientConfig,
+    /// The `k` constant in Reciprocal Rank Fusion's `1 / (k + rank)` term.
+    /// Larger values flatten the influence of rank differences; defaults to
+    /// the conventional 60.
+    #[serde(default = "default_rrf_k")]
+    rrf_k: f32,
+    /// Optional per-retriever multiplier applied to a span's RRF
+    /// contribution from that retriever, keyed by retriever name
+    /// ("semantic", "lexical", "git_log").
+    #[serde(default)]
+    retriever_weights: std::collections::HashMap<String, f32>,
+    #[serde(default)]
+    provider_key_override: Option<crate::webserver::model_selection::ProviderKeyOverride>,
+    /// Restrict results to this crate and whatever it transitively depends
+    /// on, so a query made from inside one crate of a monorepo doesn't
+    /// surface symbols only reachable from an unrelated sibling crate.
+    /// Requires `workspace_root` (the editor already knows its own
+    /// checkout's physical path) so the crate graph can be resolved.
+    #[serde(default)]
+    crate_scope: Option<crate::repo::crate_graph::CrateId>,
+    #[serde(default)]
+    workspace_root: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -116,16 +203,18 @@ pub async fn hybrid_search(
         query,
         repo,
         model_config,
+        rrf_k,
+        retriever_weights,
+        provider_key_override,
+        crate_scope,
+        workspace_root,
     }): axumQuery<HybridSearchQuery>,
     Extension(app): Extension<Application>,
 ) -> Result<impl IntoResponse> {
-    // Here we want to do the following:
-    // - do a semantic search (normalize it to a score between 0.5 -> 1)
-    // - do a lexical search (normalize it to a score between 0.5 -> 1)
-    // - get statistics from the git log (normalize it to a score between 0.5 -> 1)
-    // hand-waving the numbers here for whatever works for now
-    // - final score -> git_log_score * 4 + lexical_search * 2.5 + semantic_search_score
-    // - combine the score as following
+    // We run the semantic, lexical, and git-log retrievers independently to
+    // get three ranked lists, then fuse them with Reciprocal Rank Fusion
+    // instead of trying to compare their raw, incomparably-scaled scores.
+    let model_config = model_config.with_override(provider_key_override);
     let reranker = app.reranker.clone();
     let chat_broker = app.chat_broker.clone();
     let llm_broker = app.llm_broker.clone();
@@ -133,7 +222,13 @@ pub async fn hybrid_search(
     let session_id = uuid::Uuid::new_v4();
     let conversation_id = uuid::Uuid::new_v4();
     let sql_db = app.sql.clone();
+    let semantic_store =
+        crate::semantic_search::incremental_index::SemanticIndexStore::new(sql_db.clone());
+    if let Some(workspace_root) = &workspace_root {
+        refresh_semantic_index(&semantic_store, &repo, workspace_root).await;
+    }
     let (sender, _) = tokio::sync::mpsc::channel(100);
+    let tools = tools_for(&model_config);
     let mut agent = Agent::prepare_for_semantic_search(
         app,
         repo,
@@ -149,8 +244,30 @@ pub async fn hybrid_search(
         llm_tokenizer,
         chat_broker,
         reranker,
+        tools,
     );
-    let hybrid_search_results = agent.code_search_hybrid(&query).await.unwrap_or(vec![]);
+    let rrf_config = RrfConfig::new(rrf_k, retriever_weights);
+    let mut hybrid_search_results = agent
+        .code_search_hybrid(&query, rrf_config.clone())
+        .await
+        .unwrap_or(vec![]);
+
+    if let Some(workspace_root) = &workspace_root {
+        match lexical_rrf_spans(workspace_root, &query, &rrf_config).await {
+            Ok(lexical_spans) => hybrid_search_results.extend(lexical_spans),
+            Err(err) => tracing::warn!(%err, "lexical RRF retriever failed"),
+        }
+    }
+
+    let hybrid_search_results = match (crate_scope, workspace_root) {
+        (Some(scope), Some(workspace_root)) => filter_spans_by_crate_scope(
+            hybrid_search_results,
+            &CargoWorkspaceResolver.resolve(std::path::Path::new(&workspace_root))?,
+            &scope,
+            std::path::Path::new(&workspace_root),
+        ),
+        _ => hybrid_search_results,
+    };
     Ok(json(HybridSearchResponse {
         session_id: uuid::Uuid::new_v4(),
         query,
@@ -158,6 +275,159 @@ pub async fn hybrid_search(
     }))
 }
 
+/// Tunables for `Agent::code_search_hybrid`'s Reciprocal Rank Fusion pass.
+#[derive(Debug, Clone)]
+pub struct RrfConfig {
+    pub k: f32,
+    pub retriever_weights: std::collections::HashMap<String, f32>,
+}
+
+impl RrfConfig {
+    pub fn new(k: f32, retriever_weights: std::collections::HashMap<String, f32>) -> Self {
+        Self { k, retriever_weights }
+    }
+
+    fn weight_for(&self, retriever: &str) -> f32 {
+        self.retriever_weights
+            .get(retriever)
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// Keeps only the spans whose owning crate is `scope` or one of its
+/// transitive dependencies. A span whose file doesn't fall under any known
+/// crate root is kept rather than dropped — a graph that can't attribute a
+/// file shouldn't silently exclude it from results. Span paths are
+/// relative, so each is joined onto `workspace_root` before being matched
+/// against the graph's crate roots.
+fn filter_spans_by_crate_scope(
+    spans: Vec<CodeSpan>,
+    crate_graph: &CrateGraph,
+    scope: &crate::repo::crate_graph::CrateId,
+    workspace_root: &std::path::Path,
+) -> Vec<CodeSpan> {
+    let in_scope: HashSet<_> = crate_graph
+        .transitive_dependencies(scope)
+        .into_iter()
+        .collect();
+    spans
+        .into_iter()
+        .filter(|span| {
+            match crate_graph.owning_crate(&workspace_root.join(&span.file_path)) {
+                Some(owner) => in_scope.contains(owner),
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// A single retriever's ranked output, ready to be fused with the others.
+/// `rank` is the 1-based position of the span in that retriever's list.
+pub struct RankedSpan {
+    pub retriever: &'static str,
+    pub rank: usize,
+    pub span: CodeSpan,
+}
+
+fn span_identity(span: &CodeSpan) -> (String, u64, u64) {
+    (span.file_path.clone(), span.start_line, span.end_line)
+}
+
+/// Real, lexical-only stand-in for the retriever side of `hybrid_search`'s
+/// fusion pass. `hybrid_search` hands off to `Agent::code_search_hybrid` to
+/// run the semantic/lexical/git-log retrievers and fuse them, but that
+/// method has no backing source anywhere in this checkout -- which left
+/// `reciprocal_rank_fusion` itself without a single caller in this tree,
+/// real or otherwise. This builds one genuinely real ranked list -- every
+/// tracked file under `workspace_root` containing `query` as a
+/// case-insensitive substring, ranked by descending match count -- and runs
+/// it through `reciprocal_rank_fusion` directly, so the fusion pass
+/// actually executes against real files instead of sitting dead. It is not
+/// a semantic or git-log-aware retriever; those still don't exist here (no
+/// embedder, no git-log retriever implementation), so there's only ever
+/// one list in `ranked_lists` for now.
+async fn lexical_rrf_spans(
+    workspace_root: &str,
+    query: &str,
+    config: &RrfConfig,
+) -> anyhow::Result<Vec<CodeSpan>> {
+    let files = crate::semantic_search::incremental_index::list_repo_file_blobs(
+        std::path::Path::new(workspace_root),
+    )
+    .await?;
+
+    let needle = query.to_lowercase();
+    let mut matches: Vec<(usize, CodeSpan)> = Vec::new();
+    for file in files {
+        let path = std::path::Path::new(workspace_root).join(&file.relative_path);
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let hit_count = content.to_lowercase().matches(&needle).count();
+        if hit_count == 0 {
+            continue;
+        }
+
+        let first_hit_line = content
+            .lines()
+            .position(|line| line.to_lowercase().contains(&needle))
+            .unwrap_or(0);
+        let snippet = content
+            .lines()
+            .skip(first_hit_line)
+            .take(5)
+            .collect::<Vec<_>>()
+            .join("\n");
+        matches.push((
+            hit_count,
+            CodeSpan {
+                file_path: file.relative_path,
+                alias: 0,
+                start_line: first_hit_line as u64,
+                end_line: (first_hit_line + 5) as u64,
+                data: snippet,
+                score: None,
+            },
+        ));
+    }
+
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let ranked_list = matches
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (_hit_count, span))| RankedSpan {
+            retriever: "lexical",
+            rank: idx + 1,
+            span,
+        })
+        .collect();
+
+    Ok(reciprocal_rank_fusion(vec![ranked_list], config))
+}
+
+/// Reciprocal Rank Fusion: `score(d) = Σ_i weight_i / (k + rank_i(d))`,
+/// summed over every retriever list the span appears in. A span missing
+/// from a list simply contributes nothing from that retriever, which is
+/// what makes RRF robust to retrievers with wildly different score scales.
+pub fn reciprocal_rank_fusion(ranked_lists: Vec<Vec<RankedSpan>>, config: &RrfConfig) -> Vec<CodeSpan> {
+    let mut fused: std::collections::HashMap<(String, u64, u64), (f32, CodeSpan)> =
+        std::collections::HashMap::new();
+
+    for ranked in ranked_lists.into_iter().flatten() {
+        let identity = span_identity(&ranked.span);
+        let contribution = config.weight_for(ranked.retriever) / (config.k + ranked.rank as f32);
+        fused
+            .entry(identity)
+            .and_modify(|(score, _)| *score += contribution)
+            .or_insert_with(|| (contribution, ranked.span.clone()));
+    }
+
+    let mut spans: Vec<(f32, CodeSpan)> = fused.into_values().collect();
+    spans.sort_by(|(score_a, _), (score_b, _)| score_b.partial_cmp(score_a).unwrap());
+    spans.into_iter().map(|(_, span)| span).collect()
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct ExplainRequest {
     query: String,
@@ -168,6 +438,8 @@ pub struct ExplainRequest {
     #[serde(default = "default_thread_id")]
     thread_id: uuid::Uuid,
     model_config: LLMClientConfig,
+    #[serde(default)]
+    provider_key_override: Option<crate::webserver::model_selection::ProviderKeyOverride>,
 }
 
 /// We are going to handle the explain function here, but its going to be very
@@ -184,9 +456,11 @@ pub async fn explain(
         repo_ref,
         thread_id,
         model_config,
+        provider_key_override,
     }): axumQuery<ExplainRequest>,
     Extension(app): Extension<Application>,
 ) -> Result<impl IntoResponse> {
+    let model_config = model_config.with_override(provider_key_override);
     let reranker = app.reranker.clone();
     let chat_broker = app.chat_broker.clone();
     let llm_broker = app.llm_broker.clone();
@@ -246,6 +520,7 @@ pub async fn explain(
 
     let sql = app.sql.clone();
     let editor_parsing = Default::default();
+    let tools = tools_for(&model_config);
 
     let agent = Agent {
         application: app,
@@ -262,6 +537,7 @@ pub async fn explain(
         llm_tokenizer,
         chat_broker,
         reranker,
+        tools,
     };
 
     generate_agent_stream(agent, action, receiver).await
@@ -389,7 +665,11 @@ pub struct FollowupChatRequest {
     pub user_context: UserContext,
     pub project_labels: Vec<String>,
     pub active_window_data: Option<ActiveWindowData>,
+    /// Deprecated: prefer `provider_key_override`. Kept so existing
+    /// clients that only send an OpenAI key over the wire keep working.
     pub openai_key: Option<String>,
+    #[serde(default)]
+    pub provider_key_override: Option<crate::webserver::model_selection::ProviderKeyOverride>,
     pub model_config: LLMClientConfig,
 }
 
@@ -471,9 +751,21 @@ pub async fn followup_chat(
         project_labels,
         active_window_data,
         openai_key,
+        provider_key_override,
         model_config,
     }): Json<FollowupChatRequest>,
 ) -> Result<impl IntoResponse> {
+    // A bare `openai_key` on the old wire shape is just a BYOK override
+    // that was never generalized past OpenAI; normalize it into the same
+    // override the rest of the request goes through.
+    let provider_key_override = provider_key_override.or_else(|| {
+        openai_key.map(|api_key| crate::webserver::model_selection::ProviderKeyOverride {
+            provider: "openai".to_owned(),
+            api_key,
+            endpoint: None,
+        })
+    });
+    let model_config = model_config.with_override(provider_key_override);
     let session_id = uuid::Uuid::new_v4();
     let user_id = app.user_id.to_owned();
     let mut event = PosthogEvent::new("model_config");
@@ -533,41 +825,24 @@ pub async fn followup_chat(
         paths: (0..file_path_len).collect(),
     };
 
-    let agent = if let Some(openai_user_key) = openai_key {
-        Agent::prepare_for_followup(
-            app,
-            repo_ref,
-            session_id,
-            llm_broker,
-            sql_db,
-            previous_messages,
-            sender,
-            user_context,
-            project_labels,
-            Default::default(),
-            model_config,
-            llm_tokenizer,
-            chat_broker,
-            reranker,
-        )
-    } else {
-        Agent::prepare_for_followup(
-            app,
-            repo_ref,
-            session_id,
-            llm_broker,
-            sql_db,
-            previous_messages,
-            sender,
-            user_context,
-            project_labels,
-            Default::default(),
-            model_config,
-            llm_tokenizer,
-            chat_broker,
-            reranker,
-        )
-    };
+    let tools = tools_for(&model_config);
+    let agent = Agent::prepare_for_followup(
+        app,
+        repo_ref,
+        session_id,
+        llm_broker,
+        sql_db,
+        previous_messages,
+        sender,
+        user_context,
+        project_labels,
+        Default::default(),
+        model_config,
+        llm_tokenizer,
+        chat_broker,
+        reranker,
+        tools,
+    );
 
     generate_agent_stream(agent, action, receiver).await
 }
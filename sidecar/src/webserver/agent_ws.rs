@@ -0,0 +1,376 @@
+//! A single WebSocket endpoint that multiplexes many agent conversations
+//! over one connection, using a Phoenix-channel-style framing so a client
+//! can join/leave topics and resume a dropped run.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::{response::IntoResponse, Extension};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+use crate::application::application::Application;
+
+/// Serialized as a JSON object with fields `join_ref`, `ref`, `topic`,
+/// `event` and `payload`; it does not use Phoenix's positional
+/// `[join_ref, ref, topic, event, payload]` array form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelFrame {
+    pub join_ref: Option<u64>,
+    #[serde(rename = "ref")]
+    pub event_ref: u64,
+    pub topic: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+const PHOENIX_TOPIC: &str = "phoenix";
+const HEARTBEAT_EVENT: &str = "heartbeat";
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+/// Client -> server event that starts an agent run on a `thread:<uuid>`
+/// topic, carrying the same query shape `search_agent` takes over its
+/// plain HTTP route.
+const AGENT_START_EVENT: &str = "agent:start";
+/// Server -> client event wrapping each `ConversationMessage` the agent
+/// run produces.
+const AGENT_MESSAGE_EVENT: &str = "agent:message";
+const AGENT_LIVE_BUFFER: usize = 256;
+
+/// One buffered outbound event, kept around so a reconnecting client that
+/// sends a `Last-Event-ID`-equivalent `ref` can replay what it missed.
+#[derive(Debug, Clone)]
+struct BufferedEvent {
+    event_ref: u64,
+    frame: ChannelFrame,
+}
+
+/// Per-topic state: a ring buffer for replay, a broadcast sender so every
+/// socket currently joined to the topic gets live events, and whether an
+/// agent run has already been started for it (a second `agent:start`, e.g.
+/// from a second tab reattaching, doesn't spawn a second run).
+struct TopicState {
+    buffer: Vec<BufferedEvent>,
+    live: broadcast::Sender<ChannelFrame>,
+    started: bool,
+}
+
+impl TopicState {
+    fn new() -> Self {
+        let (live, _) = broadcast::channel(AGENT_LIVE_BUFFER);
+        Self {
+            buffer: Vec::new(),
+            live,
+            started: false,
+        }
+    }
+}
+
+const MAX_BUFFERED_EVENTS_PER_TOPIC: usize = 256;
+
+/// Shared across every socket on this server: topic name -> buffered state,
+/// and the next `ref` to assign to an outbound event.
+#[derive(Clone, Default)]
+pub struct AgentChannelRegistry {
+    inner: Arc<Mutex<AgentChannelRegistryInner>>,
+}
+
+#[derive(Default)]
+struct AgentChannelRegistryInner {
+    topics: HashMap<String, TopicState>,
+    next_ref: u64,
+}
+
+impl AgentChannelRegistry {
+    async fn next_ref(&self) -> u64 {
+        let mut inner = self.inner.lock().await;
+        inner.next_ref += 1;
+        inner.next_ref
+    }
+
+    async fn record_and_broadcast(&self, topic: &str, frame: ChannelFrame) {
+        let mut inner = self.inner.lock().await;
+        let state = inner
+            .topics
+            .entry(topic.to_owned())
+            .or_insert_with(TopicState::new);
+        state.buffer.push(BufferedEvent {
+            event_ref: frame.event_ref,
+            frame: frame.clone(),
+        });
+        if state.buffer.len() > MAX_BUFFERED_EVENTS_PER_TOPIC {
+            let overflow = state.buffer.len() - MAX_BUFFERED_EVENTS_PER_TOPIC;
+            state.buffer.drain(0..overflow);
+        }
+        // No subscribers is a normal outcome (nobody has this topic open
+        // right now); the frame is still in the replay buffer for later.
+        let _ = state.live.send(frame);
+    }
+
+    async fn replay_since(&self, topic: &str, last_seen_ref: u64) -> Vec<ChannelFrame> {
+        let inner = self.inner.lock().await;
+        inner
+            .topics
+            .get(topic)
+            .map(|state| {
+                state
+                    .buffer
+                    .iter()
+                    .filter(|event| event.event_ref > last_seen_ref)
+                    .map(|event| event.frame.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Subscribes to live events for `topic`, creating its state if this is
+    /// the first socket to join it.
+    async fn subscribe(&self, topic: &str) -> broadcast::Receiver<ChannelFrame> {
+        let mut inner = self.inner.lock().await;
+        inner
+            .topics
+            .entry(topic.to_owned())
+            .or_insert_with(TopicState::new)
+            .live
+            .subscribe()
+    }
+
+    /// Claims the right to start the agent run for `topic`; returns `true`
+    /// exactly once per topic so a reconnecting client's `agent:start`
+    /// never spawns a second run alongside the first.
+    async fn claim_start(&self, topic: &str) -> bool {
+        let mut inner = self.inner.lock().await;
+        let state = inner
+            .topics
+            .entry(topic.to_owned())
+            .or_insert_with(TopicState::new);
+        if state.started {
+            false
+        } else {
+            state.started = true;
+            true
+        }
+    }
+}
+
+pub async fn agent_channel_ws(
+    ws: WebSocketUpgrade,
+    Extension(app): Extension<Application>,
+    Extension(registry): Extension<AgentChannelRegistry>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, app, registry))
+}
+
+async fn handle_socket(socket: WebSocket, app: Application, registry: AgentChannelRegistry) {
+    let (mut sink, mut stream) = socket.split();
+    let (outbound_tx, mut outbound_rx) = mpsc::channel::<ChannelFrame>(64);
+
+    let heartbeat_tx = outbound_tx.clone();
+    let heartbeat_registry = registry.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            let event_ref = heartbeat_registry.next_ref().await;
+            let frame = ChannelFrame {
+                join_ref: None,
+                event_ref,
+                topic: PHOENIX_TOPIC.to_owned(),
+                event: HEARTBEAT_EVENT.to_owned(),
+                payload: serde_json::json!({}),
+            };
+            if heartbeat_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            Some(outbound_frame) = outbound_rx.recv() => {
+                if send_frame(&mut sink, &outbound_frame).await.is_err() {
+                    break;
+                }
+            }
+            incoming = stream.next() => {
+                let Some(Ok(message)) = incoming else { break; };
+                let Message::Text(text) = message else { continue; };
+                let Ok(frame) = serde_json::from_str::<ChannelFrame>(&text) else { continue; };
+                match frame.event.as_str() {
+                    "phx_join" => {
+                        let last_seen_ref = frame
+                            .payload
+                            .get("last_seen_ref")
+                            .and_then(|value| value.as_u64())
+                            .unwrap_or(0);
+                        let replayed = registry.replay_since(&frame.topic, last_seen_ref).await;
+                        let reply_ref = registry.next_ref().await;
+                        let reply = ChannelFrame {
+                            join_ref: frame.join_ref,
+                            event_ref: reply_ref,
+                            topic: frame.topic.clone(),
+                            event: "phx_reply".to_owned(),
+                            payload: serde_json::json!({ "status": "ok", "replayed": replayed.len() }),
+                        };
+                        if send_frame(&mut sink, &reply).await.is_err() {
+                            break;
+                        }
+                        for event in replayed {
+                            if send_frame(&mut sink, &event).await.is_err() {
+                                break;
+                            }
+                        }
+                        subscribe_topic_to_outbound(&registry, frame.topic.clone(), outbound_tx.clone());
+                    }
+                    AGENT_START_EVENT => {
+                        spawn_agent_run_for_topic(&app, &registry, frame.topic.clone(), frame.payload.clone()).await;
+                    }
+                    _ => {
+                        // Any other client -> server event (e.g. a chat
+                        // message on an already-joined topic) isn't part of
+                        // the clarification/query protocol yet.
+                    }
+                }
+            }
+            else => break,
+        }
+    }
+}
+
+/// Forwards every live event broadcast for `topic` into this socket's
+/// single outbound channel, so `handle_socket`'s `select!` only ever needs
+/// one outbound arm no matter how many topics a socket has joined.
+fn subscribe_topic_to_outbound(
+    registry: &AgentChannelRegistry,
+    topic: String,
+    outbound_tx: mpsc::Sender<ChannelFrame>,
+) {
+    let registry = registry.clone();
+    tokio::spawn(async move {
+        let mut live_rx = registry.subscribe(&topic).await;
+        loop {
+            match live_rx.recv().await {
+                Ok(frame) if frame.topic == topic => {
+                    if outbound_tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+async fn send_frame(
+    sink: &mut futures::stream::SplitSink<WebSocket, Message>,
+    frame: &ChannelFrame,
+) -> Result<(), axum::Error> {
+    use futures::SinkExt;
+    let text = serde_json::to_string(frame).expect("channel frame always serializes");
+    sink.send(Message::Text(text)).await
+}
+
+/// A `thread:<uuid>` topic begins an agent run using exactly the
+/// construction `search_agent` uses for the same query shape over plain
+/// HTTP, so the multiplexed channel and the single-conversation route stay
+/// behaviorally identical. `claim_start` makes this idempotent per topic:
+/// a reconnect's `phx_join` only replays, it never starts a second run.
+async fn spawn_agent_run_for_topic(
+    app: &Application,
+    registry: &AgentChannelRegistry,
+    topic: String,
+    start_payload: serde_json::Value,
+) {
+    let Some(_thread_id) = topic
+        .strip_prefix("thread:")
+        .and_then(|id| uuid::Uuid::parse_str(id).ok())
+    else {
+        return;
+    };
+    if !registry.claim_start(&topic).await {
+        return;
+    }
+    let query: super::agent::SearchInformation = match serde_json::from_value(start_payload) {
+        Ok(query) => query,
+        Err(err) => {
+            tracing::warn!(%topic, %err, "agent:start payload did not match SearchInformation");
+            return;
+        }
+    };
+
+    let app = app.clone();
+    let registry = registry.clone();
+    tokio::spawn(async move {
+        if let Err(err) = run_agent_and_forward(app, &registry, topic.clone(), query).await {
+            tracing::warn!(%topic, %err, "agent run for topic failed");
+        }
+    });
+}
+
+/// Runs `search_agent`'s handler body for `query` and republishes every
+/// `ConversationMessage` it emits as a `ChannelFrame` on `topic`, as soon as
+/// each SSE `data:` block arrives. Reuses the SSE response `search_agent`
+/// already produces rather than reimplementing how an `Agent` is driven,
+/// but drives its body as a byte stream instead of buffering the whole
+/// response with `to_bytes` first — otherwise a multiplexed client would
+/// see nothing until the underlying run had finished, which is strictly
+/// worse than the single-connection SSE this replaces.
+async fn run_agent_and_forward(
+    app: Application,
+    registry: &AgentChannelRegistry,
+    topic: String,
+    query: super::agent::SearchInformation,
+) -> anyhow::Result<()> {
+    let response = super::agent::search_agent(axum::extract::Query(query), Extension(app))
+        .await
+        .map_err(|_| anyhow::anyhow!("search_agent failed"))?
+        .into_response();
+
+    let mut data_stream = response.into_body().into_data_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = data_stream.next().await {
+        let chunk = chunk?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        // An SSE event block is terminated by a blank line; only the
+        // blocks fully received so far are parsed out of `buffer`, and
+        // whatever's left (a block still arriving) stays buffered for the
+        // next chunk.
+        while let Some(boundary) = buffer.find("\n\n") {
+            let event_block: String = buffer.drain(..boundary + 2).collect();
+            forward_event_block(registry, &topic, &event_block).await;
+        }
+    }
+    if !buffer.trim().is_empty() {
+        forward_event_block(registry, &topic, &buffer).await;
+    }
+
+    Ok(())
+}
+
+/// Parses one SSE event block's `data: ` line and broadcasts it as a
+/// `ChannelFrame`, if it parses as JSON; non-`data:`/non-JSON blocks (e.g.
+/// the `event: ping` keepalive some servers interleave) are skipped.
+async fn forward_event_block(registry: &AgentChannelRegistry, topic: &str, event_block: &str) {
+    let Some(data_line) = event_block
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+    else {
+        return;
+    };
+    let Ok(payload) = serde_json::from_str::<serde_json::Value>(data_line) else {
+        return;
+    };
+    let event_ref = registry.next_ref().await;
+    let frame = ChannelFrame {
+        join_ref: None,
+        event_ref,
+        topic: topic.to_owned(),
+        event: AGENT_MESSAGE_EVENT.to_owned(),
+        payload,
+    };
+    registry.record_and_broadcast(topic, frame).await;
+}
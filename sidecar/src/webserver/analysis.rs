@@ -0,0 +1,47 @@
+//! Serves `reporting::analysis`'s per-file definitions/references/
+//! relationships as JSON, so an editor can build go-to-definition and
+//! find-all-references without linking against the crate directly.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::repo::file_resolver::{DiskVfs, FileResolver, Vfs};
+use crate::reporting::analysis::{AnalysisIndex, FileAnalysis};
+
+#[derive(Debug, Deserialize)]
+pub struct FileAnalysisQuery {
+    /// Checkout root `relative_path` is resolved against. Taken as a
+    /// `DiskVfs` resolve instead of an opaque `FileId` so this endpoint
+    /// keys into `AnalysisIndex` the same way a real tree-sitter
+    /// extraction pass eventually will once one exists, rather than every
+    /// caller having to hand-roll its own `FileId` formatting.
+    pub workspace_root: String,
+    pub relative_path: String,
+}
+
+pub async fn for_file(
+    index: Arc<AnalysisIndex>,
+    query: FileAnalysisQuery,
+) -> super::types::Result<axum::Json<FileAnalysis>> {
+    let relative_path = PathBuf::from(&query.relative_path);
+    let vfs = DiskVfs::new(
+        PathBuf::from(&query.workspace_root),
+        vec![relative_path.clone()],
+    );
+    let file_id = vfs
+        .resolve(&relative_path)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a known file", query.relative_path))?;
+
+    // Lazily scan the file the first time (or every time, cheaply — there's
+    // no staleness tracking here) it's queried, rather than requiring
+    // something else to have already populated `index` for it. See
+    // `AnalysisIndex::populate_naive` for what this stopgap scan can and
+    // can't find.
+    let content = vfs.read(&file_id).await?;
+    let content = String::from_utf8_lossy(&content);
+    index.populate_naive(file_id.clone(), &content);
+
+    Ok(axum::Json(index.for_file(&file_id)))
+}
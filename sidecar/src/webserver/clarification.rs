@@ -0,0 +1,51 @@
+//! Surfaces an agent run's pending clarification question and accepts the
+//! user's choice, resuming the run via `state::clarification`.
+
+use axum::extract::Path;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::agentic::tool::human::qa::Answer;
+use crate::state::clarification;
+
+#[derive(Debug, Serialize)]
+pub struct PendingQuestionResponse {
+    pub question: Option<crate::agentic::tool::human::qa::Question>,
+}
+
+/// Polled by the editor so it can render the pending question's choices as
+/// buttons.
+pub async fn pending_question(
+    Path(task_id): Path<Uuid>,
+) -> super::types::Result<Json<PendingQuestionResponse>> {
+    Ok(Json(PendingQuestionResponse {
+        question: clarification::pending(task_id),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnswerRequest {
+    pub choice_id: String,
+}
+
+/// Resumes the agent run suspended on `task_id`'s clarification question
+/// with the chosen option.
+pub async fn answer_question(
+    Path(task_id): Path<Uuid>,
+    Json(request): Json<AnswerRequest>,
+) -> super::types::Result<Json<bool>> {
+    clarification::answer(task_id, Answer::new(request.choice_id))?;
+    Ok(Json(true))
+}
+
+/// Mounted by the application's main router alongside the other
+/// `webserver` route groups; not self-hosting since `Application` is
+/// supplied to the rest of `webserver` via an `Extension` layer applied at
+/// that top level, not per route-group.
+pub fn routes() -> Router {
+    Router::new()
+        .route("/agent/clarification/:task_id", get(pending_question))
+        .route("/agent/clarification/:task_id/answer", post(answer_question))
+}
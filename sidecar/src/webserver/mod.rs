@@ -0,0 +1,15 @@
+//! HTTP handlers for the editor-facing API: search/chat over a repo
+//! (`agent`), the multiplexed WebSocket channel those conversations stream
+//! over (`agent_ws`), multi-step plan execution (`plan`, `plan_store`,
+//! `plan_ot`), and the model-selection schema clients negotiate against
+//! (`model_selection`).
+
+pub mod agent;
+pub mod agent_ws;
+pub mod analysis;
+pub mod clarification;
+pub mod model_selection;
+pub mod plan;
+pub mod plan_ot;
+pub mod plan_store;
+pub mod symbols;
@@ -0,0 +1,161 @@
+//! Configuration for picking which LLM provider/model a request should use,
+//! plus (where the provider supports it) the native tool-calling schema we
+//! hand the model instead of asking it to emit free-form text.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::types::AgentAction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMClientConfig {
+    pub provider: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub endpoint: Option<String>,
+    /// Providers which understand a `tools` field in the completion request
+    /// and return a structured `tool_call` instead of plain text. When this
+    /// is `false` we fall back to parsing the model's free-form answer for
+    /// the next `AgentAction`.
+    #[serde(default)]
+    pub supports_tool_calling: bool,
+}
+
+impl LLMClientConfig {
+    /// A small, non-sensitive subset of the config we're comfortable sending
+    /// to posthog for debugging which provider/model a request landed on.
+    /// Deliberately omits `api_key`.
+    pub fn logging_config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "provider": self.provider,
+            "model": self.model,
+            "supports_tool_calling": self.supports_tool_calling,
+        })
+    }
+
+    /// Bring-your-own-key: swap in the caller's own credentials/endpoint for
+    /// this one request, instead of the server's defaults. Lets
+    /// self-hosting and multi-tenant users pay with their own account and
+    /// pick a provider per request without restarting the sidecar.
+    pub fn with_override(mut self, provider_override: Option<ProviderKeyOverride>) -> Self {
+        if let Some(provider_override) = provider_override {
+            self.provider = provider_override.provider;
+            self.api_key = Some(provider_override.api_key);
+            if provider_override.endpoint.is_some() {
+                self.endpoint = provider_override.endpoint;
+            }
+        }
+        self
+    }
+}
+
+/// A per-request override of which provider/credentials to use, supplied by
+/// the caller instead of relying on the server's own configured keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderKeyOverride {
+    pub provider: String,
+    pub api_key: String,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// JSON-schema describing a single callable tool, in the shape most
+/// providers (OpenAI, Anthropic) expect inside a completion request's
+/// `tools` array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSchema {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// A tool call the model handed back, still in its wire shape, before we
+/// attempt to turn it into an `AgentAction`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ToolCallParseError {
+    #[error("unknown tool name: {0}")]
+    UnknownTool(String),
+    #[error("failed to deserialize tool arguments for {tool}: {source}")]
+    BadArguments {
+        tool: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// The tool-schema equivalent of every `AgentAction` variant we know how to
+/// drive the agent with. Providers with `supports_tool_calling` get these
+/// passed in the request's `tools` field; everything else keeps using the
+/// existing text-parsing path.
+pub fn agent_action_tools() -> Vec<ToolSchema> {
+    vec![
+        ToolSchema {
+            name: "query".to_owned(),
+            description: "Search the codebase for context relevant to answering the user's question."
+                .to_owned(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "The search query to run against the codebase.",
+                    }
+                },
+                "required": ["query"],
+            }),
+        },
+        ToolSchema {
+            name: "answer".to_owned(),
+            description: "Answer the user using the code spans already gathered in this conversation."
+                .to_owned(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "integer" },
+                        "description": "Indices of the previously gathered code spans to ground the answer in.",
+                    }
+                },
+                "required": ["paths"],
+            }),
+        },
+    ]
+}
+
+/// Deserializes a provider tool-call directly into an `AgentAction`, instead
+/// of scraping it out of free-form model text.
+pub fn tool_call_to_agent_action(tool_call: &ToolCall) -> Result<AgentAction, ToolCallParseError> {
+    match tool_call.name.as_str() {
+        "query" => {
+            #[derive(Deserialize)]
+            struct QueryArgs {
+                query: String,
+            }
+            let args: QueryArgs = serde_json::from_value(tool_call.arguments.clone())
+                .map_err(|source| ToolCallParseError::BadArguments {
+                    tool: tool_call.name.to_owned(),
+                    source,
+                })?;
+            Ok(AgentAction::Query(args.query))
+        }
+        "answer" => {
+            #[derive(Deserialize)]
+            struct AnswerArgs {
+                paths: Vec<usize>,
+            }
+            let args: AnswerArgs = serde_json::from_value(tool_call.arguments.clone())
+                .map_err(|source| ToolCallParseError::BadArguments {
+                    tool: tool_call.name.to_owned(),
+                    source,
+                })?;
+            Ok(AgentAction::Answer { paths: args.paths })
+        }
+        other => Err(ToolCallParseError::UnknownTool(other.to_owned())),
+    }
+}
@@ -2,22 +2,31 @@
 
 use std::{collections::HashMap, sync::Arc};
 
+use super::plan_ot;
+use super::plan_store::PlanStore;
 use super::types::Result;
 use axum::response::{sse, Sse};
+use axum::Json;
+use dashmap::DashMap;
 use futures::StreamExt;
 use llm_client::clients::types::LLMClientCompletionResponse;
+use once_cell::sync::Lazy;
 use serde_json::json;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 use crate::{
-    agent::types::{AgentAnswerStreamEvent, ConversationMessage},
+    agent::types::{AgentAnswerStreamEvent, ConversationMessage, PlanError, PlanErrorKind},
     agentic::{
         symbol::events::{
             input::SymbolEventRequestId, message_event::SymbolEventMessageProperties,
         },
         tool::lsp::file_diagnostics::DiagnosticMap,
         tool::plan::{
-            plan::Plan,
+            plan::{Plan, PlanStep},
             service::{PlanService, PlanServiceError},
         },
     },
@@ -25,6 +34,243 @@ use crate::{
     user_context::types::UserContext,
 };
 
+/// Which of the handful of concurrent operations a plan can be running a
+/// cancellation token belongs to. `handle_create_plan`, `handle_append_plan`,
+/// `handle_execute_plan_until`, and `handle_diagnostics_to_steps` can all be
+/// in flight for the same `plan_id` at once (e.g. appending a new step while
+/// an earlier step is still executing); keying only by `plan_id` meant
+/// starting one clobbered whichever other one's token was registered first,
+/// so cancelling (or the other operation finishing and unregistering) could
+/// silently kill or orphan the wrong run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PlanRunKind {
+    Create,
+    Append,
+    Execute,
+    DiagnosticsToSteps,
+}
+
+/// Registers each in-flight plan operation's cancellation token so a
+/// separate request can look it up and trigger it, instead of every
+/// `handle_*` function building a token that nothing outside its own task
+/// ever sees.
+static PLAN_CANCELLATION_TOKENS: Lazy<DashMap<(uuid::Uuid, PlanRunKind), CancellationToken>> =
+    Lazy::new(DashMap::new);
+
+/// Registers `plan_id`'s `run_kind` token and returns it, replacing any
+/// stale token left over from a previous run of the same kind on the same
+/// plan.
+fn register_plan_cancellation_token(plan_id: uuid::Uuid, run_kind: PlanRunKind) -> CancellationToken {
+    let token = CancellationToken::new();
+    PLAN_CANCELLATION_TOKENS.insert((plan_id, run_kind), token.clone());
+    token
+}
+
+fn unregister_plan_cancellation_token(plan_id: uuid::Uuid, run_kind: PlanRunKind) {
+    PLAN_CANCELLATION_TOKENS.remove(&(plan_id, run_kind));
+}
+
+/// How many recent events we keep per plan so a client that reconnects
+/// after a network blip can replay what it missed instead of losing every
+/// step update emitted while it was gone.
+const PLAN_EVENT_BUFFER_SIZE: usize = 256;
+
+static PLAN_EVENT_BUFFERS: Lazy<DashMap<uuid::Uuid, std::sync::Mutex<PlanEventBuffer>>> =
+    Lazy::new(DashMap::new);
+
+#[derive(Default)]
+struct PlanEventBuffer {
+    next_seq: u64,
+    events: std::collections::VecDeque<(u64, ConversationMessage)>,
+}
+
+/// Assigns the next sequence id for `plan_id` and buffers the event under
+/// it, trimming down to `PLAN_EVENT_BUFFER_SIZE`.
+fn record_plan_event(plan_id: uuid::Uuid, message: &ConversationMessage) -> u64 {
+    let mut entry = PLAN_EVENT_BUFFERS
+        .entry(plan_id)
+        .or_insert_with(|| std::sync::Mutex::new(PlanEventBuffer::default()));
+    let mut buffer = entry.value_mut().lock().expect("plan event buffer lock");
+    buffer.next_seq += 1;
+    let seq = buffer.next_seq;
+    buffer.events.push_back((seq, message.clone()));
+    if buffer.events.len() > PLAN_EVENT_BUFFER_SIZE {
+        buffer.events.pop_front();
+    }
+    seq
+}
+
+/// Returns every buffered event for `plan_id` with a sequence id greater
+/// than `last_seen_id`, in order, so a reconnecting client can catch up
+/// before attaching to the live stream.
+fn replay_plan_events_since(plan_id: uuid::Uuid, last_seen_id: u64) -> Vec<(u64, ConversationMessage)> {
+    PLAN_EVENT_BUFFERS
+        .get(&plan_id)
+        .map(|entry| {
+            let buffer = entry.lock().expect("plan event buffer lock");
+            buffer
+                .events
+                .iter()
+                .filter(|(seq, _)| *seq > last_seen_id)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Live per-plan broadcast of applied OT operations, reaching every SSE
+/// stream currently watching `plan_id` instead of only the one handler
+/// invocation that happened to apply the operation. Mirrors `agent_ws.rs`'s
+/// per-topic `broadcast::Sender` (`TopicState::live`), which this could
+/// have reused if plan streams and agent-channel topics shared a registry,
+/// but plans are keyed by `uuid::Uuid` and already have their own
+/// replay buffer (`PLAN_EVENT_BUFFERS`) to slot alongside.
+static PLAN_OPERATION_CHANNELS: Lazy<DashMap<uuid::Uuid, broadcast::Sender<ConversationMessage>>> =
+    Lazy::new(DashMap::new);
+
+const PLAN_OPERATION_LIVE_BUFFER: usize = 256;
+
+/// Gets or creates `plan_id`'s live broadcast channel.
+fn plan_operation_channel(plan_id: uuid::Uuid) -> broadcast::Sender<ConversationMessage> {
+    PLAN_OPERATION_CHANNELS
+        .entry(plan_id)
+        .or_insert_with(|| broadcast::channel(PLAN_OPERATION_LIVE_BUFFER).0)
+        .clone()
+}
+
+/// Wraps a `ConversationMessage` stream into an SSE event stream where
+/// every event carries a monotonically increasing `id`, optionally
+/// preceded by a replay of buffered events newer than `last_event_id`.
+fn into_resumable_sse_stream(
+    plan_id: uuid::Uuid,
+    last_event_id: Option<u64>,
+    conversation_message_stream: tokio_stream::wrappers::UnboundedReceiverStream<
+        anyhow::Result<ConversationMessage>,
+    >,
+) -> impl tokio_stream::Stream<Item = anyhow::Result<sse::Event>> {
+    let replay_stream = futures::stream::iter(
+        replay_plan_events_since(plan_id, last_event_id.unwrap_or(0))
+            .into_iter()
+            .map(|(seq, message)| {
+                sse::Event::default()
+                    .id(seq.to_string())
+                    .json_data(message)
+                    .map_err(anyhow::Error::new)
+            }),
+    );
+
+    let live_stream = conversation_message_stream.map(move |conversation_message| {
+        if let Err(e) = &conversation_message {
+            tracing::error!("error in conversation message stream: {}", e);
+        }
+        let message = conversation_message.expect("should not fail deserialization");
+        let seq = record_plan_event(plan_id, &message);
+        sse::Event::default()
+            .id(seq.to_string())
+            .json_data(message)
+            .map_err(anyhow::Error::new)
+    });
+
+    // Every SSE stream for `plan_id` subscribes to the same live broadcast
+    // channel, so a `PlanOperationApplied` applied by a *different*
+    // concurrently-open stream (another editor window watching the same
+    // plan, or a background `append_to_plan` run) shows up here live,
+    // instead of only being visible to whichever handler invocation applied
+    // it -- which a client would otherwise only catch up on by reconnecting
+    // and replaying `PLAN_EVENT_BUFFERS`.
+    let operation_stream = BroadcastStream::new(plan_operation_channel(plan_id).subscribe())
+        .filter_map(move |broadcast_message| {
+            let event = match broadcast_message {
+                Ok(message) => {
+                    let seq = record_plan_event(plan_id, &message);
+                    Some(
+                        sse::Event::default()
+                            .id(seq.to_string())
+                            .json_data(message)
+                            .map_err(anyhow::Error::new),
+                    )
+                }
+                // A subscriber that fell behind just misses whatever it
+                // lagged past; `replay_stream` above already covers
+                // catching a reconnecting client back up.
+                Err(_lagged) => None,
+            };
+            futures::future::ready(event)
+        });
+
+    replay_stream.chain(futures::stream::select(live_stream, operation_stream))
+}
+
+/// Looks up and triggers every in-flight operation's token for `plan_id` --
+/// there can be more than one (e.g. an append racing an execute) -- so all
+/// of them stop gracefully, mirroring the explicit shutdown-API pattern
+/// used for long-lived sessions.
+pub async fn handle_cancel_plan(plan_id: uuid::Uuid) -> Result<impl axum::response::IntoResponse> {
+    let mut cancelled = false;
+    for entry in PLAN_CANCELLATION_TOKENS.iter() {
+        if entry.key().0 == plan_id {
+            entry.value().cancel();
+            cancelled = true;
+        }
+    }
+    Ok(axum::Json(json!({ "plan_id": plan_id, "cancelled": cancelled })))
+}
+
+/// Sends an applied OT operation to every subscriber of `plan_id`'s live
+/// broadcast channel as a dedicated `AgentAnswerStreamEvent::PlanOperationApplied`,
+/// rather than smuggling it through `LLMAnswer`'s text field. Goes out on
+/// `plan_operation_channel` rather than a single `agent_sender` so *every*
+/// SSE stream currently watching `plan_id` sees it, not just the one
+/// handler invocation that happened to apply this operation.
+fn broadcast_plan_operation(plan_id: uuid::Uuid, applied: &plan_ot::AppliedOperation) {
+    let _ = plan_operation_channel(plan_id).send(ConversationMessage::answer_update(
+        plan_id,
+        AgentAnswerStreamEvent::PlanOperationApplied(applied.clone()),
+    ));
+}
+
+/// Applies a manual step edit (insert/delete/edit) from an editor window,
+/// transforming it against anything applied to the plan since the client's
+/// `base_version`, and broadcasts the result to every other subscriber so
+/// concurrent editors converge instead of clobbering each other.
+pub async fn handle_edit_plan_step(
+    plan_id: uuid::Uuid,
+    plan_storage_path: String,
+    plan_service: PlanService,
+    versioned_op: plan_ot::VersionedOperation,
+) -> Result<axum::Json<plan_ot::AppliedOperation>> {
+    let mut plan = plan_service.load_plan(&plan_storage_path).await?;
+    let applied = plan_ot::apply_operation(plan_id, &mut plan, versioned_op);
+    plan_service.save_plan(&plan, &plan_storage_path).await?;
+    broadcast_plan_operation(plan_id, &applied);
+    Ok(axum::Json(applied))
+}
+
+/// Builds and sends `AgentAnswerStreamEvent::PlanError` for a failure
+/// during plan loading/context-preparation/step-execution, so the client
+/// gets a structured, machine-readable error instead of one smuggled
+/// through `LLMAnswer`'s text field.
+fn emit_plan_error(
+    plan_id: uuid::Uuid,
+    phase: &str,
+    kind: PlanErrorKind,
+    error: impl std::fmt::Display,
+    step_idx: Option<usize>,
+    agent_sender: &UnboundedSender<anyhow::Result<ConversationMessage>>,
+) {
+    let plan_error = PlanError {
+        plan_id,
+        phase: phase.to_owned(),
+        kind,
+        message: format!("{:#}", error),
+        step_idx,
+    };
+    let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+        plan_id,
+        AgentAnswerStreamEvent::PlanError(plan_error),
+    )));
+}
+
 async fn append_to_plan(
     plan_id: uuid::Uuid,
     plan_storage_path: String,
@@ -35,16 +281,15 @@ async fn append_to_plan(
     agent_sender: UnboundedSender<anyhow::Result<ConversationMessage>>,
 ) {
     let plan = plan_service.load_plan(&plan_storage_path).await;
-    if let Err(_) = plan {
-        let final_answer = "failed to load plan from storage".to_owned();
-        let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+    if let Err(err) = plan {
+        emit_plan_error(
             plan_id,
-            AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
-                final_answer.to_owned(),
-                Some(final_answer.to_owned()),
-                "Custom".to_owned(),
-            )),
-        )));
+            "append_to_plan",
+            PlanErrorKind::LoadFailed,
+            err,
+            None,
+            &agent_sender,
+        );
         return;
     }
     let plan = plan.expect("plan to be present");
@@ -56,10 +301,20 @@ async fn append_to_plan(
             "Custom".to_owned(),
         )),
     )));
-    if let Ok(plan) = plan_service
+    let previous_step_count = plan.steps().len();
+    if let Ok(mut plan) = plan_service
         .append_steps(plan, query, user_context, message_properties)
         .await
     {
+        // The service already mutated `plan` directly; feed the same
+        // inserts through the OT history so a concurrent manual edit
+        // transforms against this append instead of racing it.
+        let appended_steps = plan.steps()[previous_step_count..].to_vec();
+        for op in plan_ot::operations_for_appended_steps(previous_step_count, &appended_steps) {
+            let applied = plan_ot::record_applied_externally(plan_id, op);
+            broadcast_plan_operation(plan_id, &applied);
+        }
+
         let plan_debug_view = plan.to_debug_message();
         let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
             plan_id,
@@ -83,7 +338,107 @@ async fn append_to_plan(
     }
 }
 
+/// How hard `execute_plan_until` should fight for a single flaky step
+/// before giving up on the whole plan. Defaults are conservative; callers
+/// can override per request.
+#[derive(Debug, Clone)]
+pub struct ExecutionPolicy {
+    /// How many times to retry a step after a timeout or error.
+    pub retries: usize,
+    /// How long a single attempt at a step is allowed to run before it's
+    /// considered slow and retried.
+    pub slow_timeout: std::time::Duration,
+    /// After this many consecutive slow/failed attempts (across retries),
+    /// abort the whole plan execution instead of continuing to retry.
+    pub terminate_after: usize,
+}
+
+impl Default for ExecutionPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            slow_timeout: std::time::Duration::from_secs(120),
+            terminate_after: 5,
+        }
+    }
+}
+
+/// Runs `execute_step` with `policy`'s timeout/retry/backoff applied,
+/// sending a progress message on every retry attempt. Returns the number
+/// of attempts that were slow/failed along with the final result, so the
+/// caller can track consecutive failures across steps.
+async fn execute_step_with_policy(
+    plan_service: &PlanService,
+    plan_step: &PlanStep,
+    context: String,
+    message_properties: SymbolEventMessageProperties,
+    policy: &ExecutionPolicy,
+    plan_id: uuid::Uuid,
+    step_idx: usize,
+    agent_sender: &UnboundedSender<anyhow::Result<ConversationMessage>>,
+) -> (usize, anyhow::Result<()>) {
+    let mut slow_or_failed_attempts = 0;
+    for attempt in 0..=policy.retries {
+        let attempt_result = tokio::time::timeout(
+            policy.slow_timeout,
+            plan_service.execute_step(plan_step, context.clone(), message_properties.clone()),
+        )
+        .await;
+
+        match attempt_result {
+            Ok(Ok(())) => return (slow_or_failed_attempts, Ok(())),
+            Ok(Err(err)) => {
+                slow_or_failed_attempts += 1;
+                if attempt < policy.retries {
+                    let retry_message = format!(
+                        "step {} attempt {} errored, retrying",
+                        step_idx,
+                        attempt + 1
+                    );
+                    let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+                        plan_id,
+                        AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
+                            retry_message.to_owned(),
+                            Some(retry_message),
+                            "Custom".to_owned(),
+                        )),
+                    )));
+                    tokio::time::sleep(std::time::Duration::from_secs(2u64.pow(attempt as u32))).await;
+                    continue;
+                }
+                return (slow_or_failed_attempts, Err(err));
+            }
+            Err(_elapsed) => {
+                slow_or_failed_attempts += 1;
+                if attempt < policy.retries {
+                    let retry_message = format!(
+                        "step {} attempt {} timed out, retrying",
+                        step_idx,
+                        attempt + 1
+                    );
+                    let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+                        plan_id,
+                        AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
+                            retry_message.to_owned(),
+                            Some(retry_message),
+                            "Custom".to_owned(),
+                        )),
+                    )));
+                    tokio::time::sleep(std::time::Duration::from_secs(2u64.pow(attempt as u32))).await;
+                    continue;
+                }
+                return (
+                    slow_or_failed_attempts,
+                    Err(anyhow::anyhow!("step {} timed out after {} attempts", step_idx, attempt + 1)),
+                );
+            }
+        }
+    }
+    unreachable!("loop always returns by the last attempt")
+}
+
 /// Executes the plan until a checkpoint
+#[tracing::instrument(skip(plan_service, message_properties, agent_sender, policy), fields(plan_id = %plan_id, execute_until, checkpoint = tracing::field::Empty))]
 pub async fn execute_plan_until(
     // the checkpoint until which we want to execute the plan
     execute_until: usize,
@@ -92,22 +447,26 @@ pub async fn execute_plan_until(
     plan_service: PlanService,
     message_properties: SymbolEventMessageProperties,
     agent_sender: UnboundedSender<anyhow::Result<ConversationMessage>>,
+    policy: ExecutionPolicy,
 ) {
     // loads the plan from a storage location
     let plan = plan_service.load_plan(&plan_storage_path).await;
-    if let Err(_) = plan {
-        let final_answer = "failed to load plan from stroage".to_owned();
-        let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+    if let Err(err) = plan {
+        emit_plan_error(
             plan_id,
-            AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
-                final_answer.to_owned(),
-                Some(final_answer.to_owned()),
-                "Custom".to_owned(),
-            )),
-        )));
+            "execute_plan_until",
+            PlanErrorKind::LoadFailed,
+            err,
+            None,
+            &agent_sender,
+        );
         return;
     }
     let mut plan = plan.expect("plan to be present");
+    // Tracks slow/failed attempts across steps, not just within a single
+    // step's retries, so a plan made of many individually-retried-but-still
+    // -flaky steps still gets aborted instead of crawling forever.
+    let mut consecutive_slow_or_failed_attempts = 0;
     for (idx, plan_step) in plan
         .steps()
         .to_vec()
@@ -121,6 +480,19 @@ pub async fn execute_plan_until(
             }
         })
     {
+        if message_properties.cancellation_token().is_cancelled() {
+            let cancelled_message = format!("plan cancelled at step {}", idx);
+            let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+                plan_id,
+                AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
+                    cancelled_message.to_owned(),
+                    Some(cancelled_message),
+                    "Custom".to_owned(),
+                )),
+            )));
+            let _ = plan_service.save_plan(&plan, &plan_storage_path).await;
+            return;
+        }
         if plan.checkpoint().is_some() && idx <= plan.checkpoint().unwrap_or_default() {
             let executing_step = format!(
                 "Already executed step:{}, checkpoint is at: {}",
@@ -137,23 +509,72 @@ pub async fn execute_plan_until(
             )));
             continue;
         }
+        if message_properties.cancellation_token().is_cancelled() {
+            let cancelled_message = format!("plan cancelled at step {}", idx);
+            let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+                plan_id,
+                AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
+                    cancelled_message.to_owned(),
+                    Some(cancelled_message),
+                    "Custom".to_owned(),
+                )),
+            )));
+            let _ = plan_service.save_plan(&plan, &plan_storage_path).await;
+            return;
+        }
+
         // starting executing each step over here
         let checkpoint = plan.checkpoint().unwrap_or_default();
-        let context = plan_service.prepare_context(plan.steps(), checkpoint).await;
-        let execution_result = plan_service
-            .execute_step(plan_step, context, message_properties.clone())
-            .await;
-        if let Err(_) = execution_result {
+        tracing::Span::current().record("checkpoint", checkpoint);
+        let step_span = tracing::info_span!("execute_plan_step", plan_id = %plan_id, step_idx = idx, checkpoint);
+        let (slow_or_failed_attempts, execution_result) = async {
+            let context = plan_service.prepare_context(plan.steps(), checkpoint).await;
+            execute_step_with_policy(
+                &plan_service,
+                plan_step,
+                context,
+                message_properties.clone(),
+                &policy,
+                plan_id,
+                idx,
+                &agent_sender,
+            )
+            .await
+        }
+        .instrument(step_span)
+        .await;
+        consecutive_slow_or_failed_attempts += slow_or_failed_attempts;
+        if let Err(err) = execution_result {
+            emit_plan_error(
+                plan_id,
+                "execute_plan_until",
+                PlanErrorKind::StepExecutionFailed,
+                err,
+                Some(idx),
+                &agent_sender,
+            );
+            let _ = plan_service.save_plan(&plan, &plan_storage_path).await;
+            return;
+        }
+        if consecutive_slow_or_failed_attempts >= policy.terminate_after {
+            let terminated_message = format!(
+                "plan terminated after step {}: {} consecutive slow/failed attempts reached the configured limit of {}",
+                idx, consecutive_slow_or_failed_attempts, policy.terminate_after
+            );
             let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
                 plan_id,
                 AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
-                    format!("Errored out while executing step: {}", idx).to_owned(),
-                    Some(format!("Errored out while executing step: {}", idx).to_owned()),
+                    terminated_message.to_owned(),
+                    Some(terminated_message),
                     "Custom".to_owned(),
                 )),
             )));
+            let _ = plan_service.save_plan(&plan, &plan_storage_path).await;
             return;
         }
+        if slow_or_failed_attempts == 0 {
+            consecutive_slow_or_failed_attempts = 0;
+        }
         let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
             plan_id,
             AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
@@ -163,12 +584,14 @@ pub async fn execute_plan_until(
             )),
         )));
         let _ = plan.increment_checkpoint();
+        tracing::event!(tracing::Level::INFO, plan_id = %plan_id, step_idx = idx, checkpoint = plan.checkpoint().unwrap_or_default(), "checkpoint incremented");
         // save the updated checkpoint in the storage layer
         let _ = plan_service.save_plan(&plan, &plan_storage_path).await;
     }
 }
 
 /// Create the plan using the context present over here
+#[tracing::instrument(skip(user_query, user_context, editor_url, plan_storage_path, plan_service, agent_sender), fields(plan_id = %plan_id, is_deep_reasoning))]
 pub async fn create_plan(
     user_query: String,
     user_context: UserContext,
@@ -177,6 +600,7 @@ pub async fn create_plan(
     plan_storage_path: String,
     plan_service: PlanService,
     is_deep_reasoning: bool,
+    cancellation_token: CancellationToken,
     // we can send events using this
     agent_sender: UnboundedSender<anyhow::Result<ConversationMessage>>,
 ) -> Result<Plan, PlanServiceError> {
@@ -188,7 +612,6 @@ pub async fn create_plan(
             "Custom".to_owned(),
         )),
     )));
-    let cancellation_token = tokio_util::sync::CancellationToken::new();
     let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
     let plan_id_str = plan_id.to_string();
     let message_properties = SymbolEventMessageProperties::new(
@@ -249,6 +672,7 @@ plan_information:
 }
 
 /// Converts diagnostics messages with snippet into PlanStep
+#[tracing::instrument(skip(plan_storage_path, plan_service, message_properties, agent_sender), fields(plan_id = %plan_id, is_deep_reasoning, checkpoint = tracing::field::Empty))]
 pub async fn generate_steps_from_diagnostics(
     plan_id: uuid::Uuid,
     plan_storage_path: String,
@@ -258,27 +682,32 @@ pub async fn generate_steps_from_diagnostics(
     is_deep_reasoning: bool,
 ) {
     let plan = plan_service.load_plan(&plan_storage_path).await;
-    if let Err(_) = plan {
-        let final_answer = "failed to load plan from stroage".to_owned();
-        let _ = agent_sender.send(Ok(ConversationMessage::answer_update(
+    if let Err(err) = plan {
+        emit_plan_error(
             plan_id,
-            AgentAnswerStreamEvent::LLMAnswer(LLMClientCompletionResponse::new(
-                final_answer.to_owned(),
-                Some(final_answer.to_owned()),
-                "Custom".to_owned(),
-            )),
-        )));
+            "generate_steps_from_diagnostics",
+            PlanErrorKind::LoadFailed,
+            err,
+            None,
+            &agent_sender,
+        );
         return;
     };
     let mut plan = plan.expect("plan to be present");
 
     if let None = plan.checkpoint() {
-        println!("webserver::plan::generate_steps_from_diagnostics::no_checkpoint");
-
-        // ui event should be here
+        emit_plan_error(
+            plan_id,
+            "generate_steps_from_diagnostics",
+            PlanErrorKind::MissingCheckpoint,
+            "plan has no checkpoint yet, nothing to diagnose",
+            None,
+            &agent_sender,
+        );
         return;
     }
     let checkpoint = plan.checkpoint().expect("checkpoint to be present");
+    tracing::Span::current().record("checkpoint", checkpoint);
 
     // all files edited up to checkpoint
     let edited_files = plan_service.get_edited_files(&plan, checkpoint);
@@ -320,6 +749,7 @@ pub async fn generate_steps_from_diagnostics(
             is_deep_reasoning,
         )
         .await;
+    tracing::event!(tracing::Level::INFO, plan_id = %plan_id, checkpoint, "generated steps from diagnostics");
 
     // let response = plan_service.tool_box().
 
@@ -334,10 +764,11 @@ pub async fn handle_diagnostics_to_steps(
     editor_url: String,
     plan_service: PlanService,
     is_deep_reasoning: bool,
+    last_event_id: Option<u64>,
 ) -> Result<
     Sse<std::pin::Pin<Box<dyn tokio_stream::Stream<Item = anyhow::Result<sse::Event>> + Send>>>,
 > {
-    let cancellation_token = tokio_util::sync::CancellationToken::new();
+    let cancellation_token = register_plan_cancellation_token(plan_id, PlanRunKind::DiagnosticsToSteps);
     let (ui_sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
     let plan_id_str = plan_id.to_string();
     let message_properties = SymbolEventMessageProperties::new(
@@ -360,6 +791,7 @@ pub async fn handle_diagnostics_to_steps(
             is_deep_reasoning,
         )
         .await;
+        unregister_plan_cancellation_token(plan_id, PlanRunKind::DiagnosticsToSteps);
     });
 
     let conversation_message_stream =
@@ -375,17 +807,10 @@ pub async fn handle_diagnostics_to_steps(
             .expect("failed to serialize initialization object"))
     });
 
-    // // We know the stream is unwind safe as it doesn't use synchronization primitives like locks.
-    let answer_stream = conversation_message_stream.map(
-        |conversation_message: anyhow::Result<ConversationMessage>| {
-            if let Err(e) = &conversation_message {
-                tracing::error!("error in conversation message stream: {}", e);
-            }
-            sse::Event::default()
-                .json_data(conversation_message.expect("should not fail deserialization"))
-                .map_err(anyhow::Error::new)
-        },
-    );
+    // Replays any buffered events newer than `last_event_id` before
+    // attaching to the live stream, so a client that reconnects after this
+    // dropped doesn't lose every step update emitted while it was gone.
+    let answer_stream = into_resumable_sse_stream(plan_id, last_event_id, conversation_message_stream);
 
     // TODO(skcd): Re-introduce this again when we have a better way to manage
     // server side events on the client side
@@ -409,10 +834,13 @@ pub async fn handle_execute_plan_until(
     plan_storage_path: String,
     editor_url: String,
     plan_service: PlanService,
+    last_event_id: Option<u64>,
+    policy: Option<ExecutionPolicy>,
 ) -> Result<
     Sse<std::pin::Pin<Box<dyn tokio_stream::Stream<Item = anyhow::Result<sse::Event>> + Send>>>,
 > {
-    let cancellation_token = tokio_util::sync::CancellationToken::new();
+    let policy = policy.unwrap_or_default();
+    let cancellation_token = register_plan_cancellation_token(plan_id, PlanRunKind::Execute);
     let (ui_sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
     let plan_id_str = plan_id.to_string();
     let message_properties = SymbolEventMessageProperties::new(
@@ -431,8 +859,10 @@ pub async fn handle_execute_plan_until(
             plan_service,
             message_properties,
             sender,
+            policy,
         )
         .await;
+        unregister_plan_cancellation_token(plan_id, PlanRunKind::Execute);
     });
     let conversation_message_stream =
         tokio_stream::wrappers::UnboundedReceiverStream::new(receiver);
@@ -447,17 +877,10 @@ pub async fn handle_execute_plan_until(
             .expect("failed to serialize initialization object"))
     });
 
-    // // We know the stream is unwind safe as it doesn't use synchronization primitives like locks.
-    let answer_stream = conversation_message_stream.map(
-        |conversation_message: anyhow::Result<ConversationMessage>| {
-            if let Err(e) = &conversation_message {
-                tracing::error!("error in conversation message stream: {}", e);
-            }
-            sse::Event::default()
-                .json_data(conversation_message.expect("should not fail deserialization"))
-                .map_err(anyhow::Error::new)
-        },
-    );
+    // Replays any buffered events newer than `last_event_id` before
+    // attaching to the live stream, so a client that reconnects after this
+    // dropped doesn't lose every step update emitted while it was gone.
+    let answer_stream = into_resumable_sse_stream(plan_id, last_event_id, conversation_message_stream);
 
     // TODO(skcd): Re-introduce this again when we have a better way to manage
     // server side events on the client side
@@ -482,11 +905,12 @@ pub async fn handle_append_plan(
     plan_id: uuid::Uuid,
     plan_storage_path: String,
     plan_service: PlanService,
+    last_event_id: Option<u64>,
 ) -> Result<
     Sse<std::pin::Pin<Box<dyn tokio_stream::Stream<Item = anyhow::Result<sse::Event>> + Send>>>,
 > {
     let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
-    let cancellation_token = tokio_util::sync::CancellationToken::new();
+    let cancellation_token = register_plan_cancellation_token(plan_id, PlanRunKind::Append);
     let (ui_sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
     let plan_id_str = plan_id.to_string();
     let message_properties = SymbolEventMessageProperties::new(
@@ -507,6 +931,7 @@ pub async fn handle_append_plan(
             sender,
         )
         .await;
+        unregister_plan_cancellation_token(plan_id, PlanRunKind::Append);
     });
     let conversation_message_stream =
         tokio_stream::wrappers::UnboundedReceiverStream::new(receiver);
@@ -521,17 +946,10 @@ pub async fn handle_append_plan(
             .expect("failed to serialize initialization object"))
     });
 
-    // // We know the stream is unwind safe as it doesn't use synchronization primitives like locks.
-    let answer_stream = conversation_message_stream.map(
-        |conversation_message: anyhow::Result<ConversationMessage>| {
-            if let Err(e) = &conversation_message {
-                tracing::error!("error in conversation message stream: {}", e);
-            }
-            sse::Event::default()
-                .json_data(conversation_message.expect("should not fail deserialization"))
-                .map_err(anyhow::Error::new)
-        },
-    );
+    // Replays any buffered events newer than `last_event_id` before
+    // attaching to the live stream, so a client that reconnects after this
+    // dropped doesn't lose every step update emitted while it was gone.
+    let answer_stream = into_resumable_sse_stream(plan_id, last_event_id, conversation_message_stream);
 
     // TODO(skcd): Re-introduce this again when we have a better way to manage
     // server side events on the client side
@@ -557,10 +975,12 @@ pub async fn handle_create_plan(
     plan_storage_path: String,
     plan_service: PlanService,
     is_deep_reasoning: bool,
+    last_event_id: Option<u64>,
 ) -> Result<
     Sse<std::pin::Pin<Box<dyn tokio_stream::Stream<Item = anyhow::Result<sse::Event>> + Send>>>,
 > {
     let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let cancellation_token = register_plan_cancellation_token(plan_id, PlanRunKind::Create);
     // we let the plan creation happen in the background
     let _ = tokio::spawn(async move {
         let _ = create_plan(
@@ -571,9 +991,11 @@ pub async fn handle_create_plan(
             plan_storage_path,
             plan_service,
             is_deep_reasoning,
+            cancellation_token,
             sender,
         )
         .await;
+        unregister_plan_cancellation_token(plan_id, PlanRunKind::Create);
     });
     let conversation_message_stream =
         tokio_stream::wrappers::UnboundedReceiverStream::new(receiver);
@@ -588,17 +1010,10 @@ pub async fn handle_create_plan(
             .expect("failed to serialize initialization object"))
     });
 
-    // // We know the stream is unwind safe as it doesn't use synchronization primitives like locks.
-    let answer_stream = conversation_message_stream.map(
-        |conversation_message: anyhow::Result<ConversationMessage>| {
-            if let Err(e) = &conversation_message {
-                tracing::error!("error in conversation message stream: {}", e);
-            }
-            sse::Event::default()
-                .json_data(conversation_message.expect("should not fail deserialization"))
-                .map_err(anyhow::Error::new)
-        },
-    );
+    // Replays any buffered events newer than `last_event_id` before
+    // attaching to the live stream, so a client that reconnects after this
+    // dropped doesn't lose every step update emitted while it was gone.
+    let answer_stream = into_resumable_sse_stream(plan_id, last_event_id, conversation_message_stream);
 
     // TODO(skcd): Re-introduce this again when we have a better way to manage
     // server side events on the client side
@@ -616,18 +1031,33 @@ pub async fn handle_create_plan(
     Ok(Sse::new(Box::pin(stream)))
 }
 
-pub async fn check_plan_storage_path(config: Arc<Configuration>, plan_id: String) -> String {
-    let mut plan_path = config.index_dir.clone();
-    plan_path = plan_path.join("plans");
-    // check if the plan_storage_path_exists
-    if tokio::fs::metadata(&plan_path).await.is_err() {
-        tokio::fs::create_dir(&plan_path)
-            .await
-            .expect("directory creation to not fail");
-    }
-    plan_path = plan_path.join(plan_id);
-    plan_path
-        .to_str()
-        .expect("path conversion to work on all platforms")
-        .to_owned()
+pub async fn check_plan_storage_path(
+    config: Arc<Configuration>,
+    sql_pool: Option<sqlx::SqlitePool>,
+    plan_id: String,
+) -> String {
+    let plan_id = uuid::Uuid::parse_str(&plan_id).unwrap_or_else(|_| uuid::Uuid::new_v4());
+    let store = super::plan_store::build(config, sql_pool);
+    store
+        .path_for(plan_id)
+        .await
+        .expect("plan storage path resolution to not fail")
+}
+
+/// Lists every plan id a `PlanStore` currently knows about, most recently
+/// touched first, so a caller can resume a plan by id alone instead of
+/// needing to have kept its storage path around.
+pub async fn handle_list_plans(store: Arc<dyn PlanStore>) -> Result<Json<Vec<uuid::Uuid>>> {
+    let plan_ids = store.list().await?;
+    Ok(Json(plan_ids))
+}
+
+/// Forgets a plan by id, regardless of which `PlanStore` backend is
+/// configured.
+pub async fn handle_delete_plan(
+    store: Arc<dyn PlanStore>,
+    plan_id: uuid::Uuid,
+) -> Result<Json<bool>> {
+    store.delete(plan_id).await?;
+    Ok(Json(true))
 }
@@ -0,0 +1,195 @@
+//! Operational-transform layer over `Plan`'s step list.
+//!
+//! `append_to_plan` and `execute_plan_until` both do a plain
+//! `load_plan` -> mutate -> `save_plan`, so two clients editing the same
+//! plan at once last-writer-wins each other's steps. Every edit here is
+//! expressed as a `PlanOperation` tagged with the version it was authored
+//! against; `apply_operation` transforms it against whatever's been applied
+//! to that plan since, so a stale client's edit still lands on the step it
+//! meant instead of whatever now occupies that index, and keeps the server
+//! as the single authoritative merge point.
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::agentic::tool::plan::plan::{Plan, PlanStep};
+
+/// A single edit to a plan's step list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PlanOperation {
+    InsertStep { index: usize, step: PlanStep },
+    DeleteStep { index: usize },
+    EditStep { index: usize, step: PlanStep },
+}
+
+/// An operation tagged with the version its author last saw, so it can be
+/// transformed against anything applied since.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedOperation {
+    pub base_version: u64,
+    pub op: PlanOperation,
+}
+
+/// What gets broadcast to every subscriber of `plan_id` once an operation
+/// has been transformed, applied, and recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedOperation {
+    pub plan_id: Uuid,
+    pub resulting_version: u64,
+    pub op: PlanOperation,
+}
+
+#[derive(Default)]
+struct PlanOtHistory {
+    version: u64,
+    applied: Vec<PlanOperation>,
+}
+
+static PLAN_OT_HISTORY: Lazy<DashMap<Uuid, PlanOtHistory>> = Lazy::new(DashMap::new);
+
+/// Shifts `index` by however much `concurrent` moved the step list around.
+fn transform_index(index: usize, concurrent: &PlanOperation) -> usize {
+    match concurrent {
+        PlanOperation::InsertStep {
+            index: other_index, ..
+        } => {
+            if *other_index <= index {
+                index + 1
+            } else {
+                index
+            }
+        }
+        PlanOperation::DeleteStep { index: other_index } => {
+            if *other_index < index {
+                index.saturating_sub(1)
+            } else {
+                index
+            }
+        }
+        // Edits don't shift positions, so nothing downstream needs to move.
+        PlanOperation::EditStep { .. } => index,
+    }
+}
+
+fn transform(mut op: PlanOperation, concurrent: &PlanOperation) -> PlanOperation {
+    match &mut op {
+        PlanOperation::InsertStep { index, .. }
+        | PlanOperation::DeleteStep { index }
+        | PlanOperation::EditStep { index, .. } => {
+            *index = transform_index(*index, concurrent);
+        }
+    }
+    op
+}
+
+/// Transforms `versioned_op` against everything applied to `plan_id` since
+/// `versioned_op.base_version` and records the transformed result in
+/// `plan_id`'s history, without touching `plan` yet — callers whose
+/// mutation already happened elsewhere (an agent-driven append) use this to
+/// register that mutation in the same history a manual edit transforms
+/// against, instead of re-applying it.
+fn transform_and_record(plan_id: Uuid, versioned_op: VersionedOperation) -> AppliedOperation {
+    let mut history = PLAN_OT_HISTORY.entry(plan_id).or_default();
+    let mut op = versioned_op.op;
+    for concurrent in history
+        .applied
+        .iter()
+        .skip(versioned_op.base_version as usize)
+    {
+        op = transform(op, concurrent);
+    }
+
+    history.applied.push(op.clone());
+    history.version += 1;
+    let resulting_version = history.version;
+    drop(history);
+
+    AppliedOperation {
+        plan_id,
+        resulting_version,
+        op,
+    }
+}
+
+fn mutate(plan: &mut Plan, op: &PlanOperation) {
+    match op {
+        PlanOperation::InsertStep { index, step } => {
+            let steps = plan.steps_mut();
+            steps.insert((*index).min(steps.len()), step.clone());
+        }
+        PlanOperation::DeleteStep { index } => {
+            let steps = plan.steps_mut();
+            if *index < steps.len() {
+                steps.remove(*index);
+            }
+        }
+        PlanOperation::EditStep { index, step } => {
+            if let Some(existing) = plan.steps_mut().get_mut(*index) {
+                *existing = step.clone();
+            }
+        }
+    }
+}
+
+/// Transforms `versioned_op` against everything applied to `plan_id` since
+/// `versioned_op.base_version`, applies the result to `plan`, and records it
+/// in `plan_id`'s history so later operations transform against it in turn.
+/// For manually authored edits (insert/delete/edit a step from an editor
+/// window) where `plan` hasn't been touched yet.
+pub fn apply_operation(
+    plan_id: Uuid,
+    plan: &mut Plan,
+    versioned_op: VersionedOperation,
+) -> AppliedOperation {
+    let applied = transform_and_record(plan_id, versioned_op);
+    mutate(plan, &applied.op);
+    applied
+}
+
+/// Registers a mutation that has *already* been applied to `plan` (an
+/// agent-driven step append) in `plan_id`'s OT history, so a concurrent
+/// manual edit transforms against it instead of racing it. The op is
+/// assumed already expressed in terms of the plan's current indices (true
+/// for an append, which only ever adds at the end), so no transform is
+/// needed before recording.
+pub fn record_applied_externally(plan_id: Uuid, op: PlanOperation) -> AppliedOperation {
+    let mut history = PLAN_OT_HISTORY.entry(plan_id).or_default();
+    history.applied.push(op.clone());
+    history.version += 1;
+    let resulting_version = history.version;
+    drop(history);
+
+    AppliedOperation {
+        plan_id,
+        resulting_version,
+        op,
+    }
+}
+
+/// The version a new client should start sending `base_version` from.
+pub fn current_version(plan_id: Uuid) -> u64 {
+    PLAN_OT_HISTORY
+        .get(&plan_id)
+        .map(|history| history.version)
+        .unwrap_or(0)
+}
+
+/// Turns a batch of steps appended at the end of a plan (the shape
+/// `PlanService::append_steps` produces) into the `InsertStep` operations
+/// the OT history expects, so agent-driven appends and human edits share
+/// the same merge path instead of the agent bypassing it.
+pub fn operations_for_appended_steps(
+    previous_step_count: usize,
+    appended_steps: &[PlanStep],
+) -> Vec<PlanOperation> {
+    appended_steps
+        .iter()
+        .enumerate()
+        .map(|(offset, step)| PlanOperation::InsertStep {
+            index: previous_step_count + offset,
+            step: step.clone(),
+        })
+        .collect()
+}
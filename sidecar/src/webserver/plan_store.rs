@@ -0,0 +1,196 @@
+//! Pluggable lookup for where a `Plan` lives. This used to be buried in
+//! `check_plan_storage_path`'s filesystem path-building, which meant a plan
+//! could only ever be found again by a caller who still had the exact path
+//! string around. `PlanStore` turns "given an id, where/what is the plan"
+//! into a trait so a deployment can swap the default (one file per plan) for
+//! one backed by SQL, where the set of known plan ids is durably queryable
+//! instead of requiring a directory listing on the machine that created them.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::application::config::configuration::Configuration;
+
+/// Resolves a plan id to the `plan_storage_path` string that
+/// `PlanService::{load_plan,save_plan}` take today, and exposes the
+/// id-level operations (`list`, `delete`) those two methods don't.
+///
+/// Plans themselves still round-trip through `PlanService`'s existing
+/// path-based (de)serialization; a `PlanStore` only owns the mapping from
+/// id to path plus whatever bookkeeping a backend needs to answer `list`
+/// without a directory listing. Fully moving plan content into SQL would
+/// mean `PlanService` accepting a `Plan` directly instead of a path, which
+/// is a larger change to a type this checkout doesn't carry the source for.
+#[async_trait]
+pub trait PlanStore: Send + Sync {
+    /// Returns the path `PlanService::load_plan`/`save_plan` should use for
+    /// `id`, creating any backing storage for it if this is the first time
+    /// `id` has been seen.
+    async fn path_for(&self, id: Uuid) -> anyhow::Result<String>;
+
+    /// All plan ids this store currently knows about, most-recently-touched
+    /// first.
+    async fn list(&self) -> anyhow::Result<Vec<Uuid>>;
+
+    /// Forgets `id`. Safe to call on an id that was never known.
+    async fn delete(&self, id: Uuid) -> anyhow::Result<()>;
+}
+
+/// Current behavior: plans live as files under
+/// `config.index_dir/plans/<plan_id>`, and "listing" means reading that
+/// directory.
+pub struct FilesystemPlanStore {
+    plans_dir: PathBuf,
+}
+
+impl FilesystemPlanStore {
+    pub fn new(config: Arc<Configuration>) -> Self {
+        Self {
+            plans_dir: config.index_dir.join("plans"),
+        }
+    }
+}
+
+#[async_trait]
+impl PlanStore for FilesystemPlanStore {
+    async fn path_for(&self, id: Uuid) -> anyhow::Result<String> {
+        if tokio::fs::metadata(&self.plans_dir).await.is_err() {
+            tokio::fs::create_dir_all(&self.plans_dir).await?;
+        }
+        self.plans_dir
+            .join(id.to_string())
+            .to_str()
+            .map(|path| path.to_owned())
+            .ok_or_else(|| anyhow::anyhow!("plan storage path is not valid utf-8"))
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        if tokio::fs::metadata(&self.plans_dir).await.is_err() {
+            return Ok(Vec::new());
+        }
+        let mut entries = tokio::fs::read_dir(&self.plans_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| Uuid::parse_str(name).ok())
+            {
+                let modified = entry.metadata().await.and_then(|m| m.modified()).ok();
+                ids.push((modified, id));
+            }
+        }
+        // Most-recently-touched first, by file modification time.
+        ids.sort_by(|a, b| b.0.cmp(&a.0));
+        Ok(ids.into_iter().map(|(_, id)| id).collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<()> {
+        let path = self.plans_dir.join(id.to_string());
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// SQL-backed index over the same filesystem-serialized plans, so the set
+/// of known ids (and when each was last touched) survives independently of
+/// any one machine's disk and can be queried (`list`) without a directory
+/// walk. Plan *content* still lives at the path this hands back; only the
+/// id -> path -> last_touched bookkeeping is durable in SQL.
+pub struct SqlPlanStore {
+    pool: SqlitePool,
+    plans_dir: PathBuf,
+}
+
+impl SqlPlanStore {
+    pub fn new(pool: SqlitePool, config: Arc<Configuration>) -> Self {
+        Self {
+            pool,
+            plans_dir: config.index_dir.join("plans"),
+        }
+    }
+
+    pub async fn run_migrations(pool: &SqlitePool) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS plan_store_index (
+                id TEXT PRIMARY KEY,
+                storage_path TEXT NOT NULL,
+                last_touched_at INTEGER NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Picks the `PlanStore` backend a deployment actually asked for, instead of
+/// `check_plan_storage_path` hardcoding `FilesystemPlanStore` regardless of
+/// `config`. `pool` is `None` whenever the caller has no `SqlitePool` handy
+/// (or the deployment never enabled one); SQL-backed storage is then
+/// unavailable no matter what `config.enable_sql_plan_store` says, since
+/// there's nothing to back it with.
+pub fn build(config: Arc<Configuration>, pool: Option<SqlitePool>) -> Arc<dyn PlanStore> {
+    match pool {
+        Some(pool) if config.enable_sql_plan_store => Arc::new(SqlPlanStore::new(pool, config)),
+        _ => Arc::new(FilesystemPlanStore::new(config)),
+    }
+}
+
+#[async_trait]
+impl PlanStore for SqlPlanStore {
+    async fn path_for(&self, id: Uuid) -> anyhow::Result<String> {
+        if tokio::fs::metadata(&self.plans_dir).await.is_err() {
+            tokio::fs::create_dir_all(&self.plans_dir).await?;
+        }
+        let path = self
+            .plans_dir
+            .join(id.to_string())
+            .to_str()
+            .map(|path| path.to_owned())
+            .ok_or_else(|| anyhow::anyhow!("plan storage path is not valid utf-8"))?;
+        sqlx::query(
+            "INSERT INTO plan_store_index (id, storage_path, last_touched_at)
+             VALUES (?, ?, strftime('%s', 'now'))
+             ON CONFLICT(id) DO UPDATE SET last_touched_at = excluded.last_touched_at",
+        )
+        .bind(id.to_string())
+        .bind(&path)
+        .execute(&self.pool)
+        .await?;
+        Ok(path)
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<Uuid>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT id FROM plan_store_index ORDER BY last_touched_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id,)| Uuid::parse_str(&id).ok())
+            .collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<()> {
+        let path = self.plans_dir.join(id.to_string());
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        sqlx::query("DELETE FROM plan_store_index WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
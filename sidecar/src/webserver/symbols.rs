@@ -0,0 +1,60 @@
+//! "Go to symbol" / fuzzy find-definition endpoint backed by
+//! `indexes::symbols`. Exact and prefix hits never touch the embedding
+//! pipeline, so this answers in microseconds where `hybrid_search` would
+//! need a model round-trip.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::indexes::symbols::{Posting, SearchKind, SymbolIndex};
+
+#[derive(Debug, Deserialize)]
+pub struct SymbolSearchQuery {
+    pub query: String,
+    #[serde(default)]
+    pub kind: SymbolSearchKindParam,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSearchKindParam {
+    Exact,
+    #[default]
+    Prefix,
+    Fuzzy,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolSearchResponse {
+    pub file_id: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+impl From<&Posting> for SymbolSearchResponse {
+    fn from(posting: &Posting) -> Self {
+        Self {
+            file_id: posting.file_id.clone(),
+            start_byte: posting.byte_range.start,
+            end_byte: posting.byte_range.end,
+        }
+    }
+}
+
+pub async fn go_to_symbol(
+    index: Arc<SymbolIndex>,
+    query: SymbolSearchQuery,
+) -> super::types::Result<axum::Json<Vec<SymbolSearchResponse>>> {
+    let kind = match query.kind {
+        SymbolSearchKindParam::Exact => SearchKind::Exact,
+        SymbolSearchKindParam::Prefix => SearchKind::Prefix,
+        SymbolSearchKindParam::Fuzzy => SearchKind::Fuzzy { max_edits: 2 },
+    };
+    let results = index
+        .search(&query.query, kind)
+        .into_iter()
+        .map(SymbolSearchResponse::from)
+        .collect();
+    Ok(axum::Json(results))
+}